@@ -0,0 +1,478 @@
+pub mod cli {
+    use std::time::Duration;
+    use clap::{Args, Parser, Subcommand};
+
+    use crate::config::config::ENV_HELP;
+    use crate::curve::curve::CurveKind;
+    use crate::interp::interp::InterpKind;
+    use crate::noise::noise::NoiseKind;
+    use crate::plot::plot::Page;
+    use crate::screen::screen::{BlendMode, Gradient, GradientBy, Splat, ToneMap};
+    use crate::spl::spl::{CircleSplineOpts, GrainDistribution, GrainOpts, HieroglyphOpts, ScriptOpts};
+
+    #[derive(Parser)]
+    #[command(name = "spline", about = "Generative sand-splines", after_help = ENV_HELP)]
+    pub struct Cli {
+        #[command(subcommand)]
+        pub mode: Mode,
+
+        /// Canvas width in pixels [default: 1000]
+        #[arg(long, global = true)]
+        pub width: Option<usize>,
+
+        /// Canvas height in pixels [default: 1000]
+        #[arg(long, global = true)]
+        pub height: Option<usize>,
+
+        /// Stroke color as A,R,G,B in 0..1, applied to every layer (mode default if omitted)
+        #[arg(long, global = true, value_parser = parse_color, allow_hyphen_values = true)]
+        pub color: Option<[f64; 4]>,
+
+        /// Gradient color as A,R,G,B, repeat for more stops; replaces the stroke color of every layer
+        #[arg(long = "gradient", global = true, value_parser = parse_color, allow_hyphen_values = true)]
+        pub gradient: Vec<[f64; 4]>,
+
+        /// What moves along the gradient: position, iteration or noise [default: position]
+        #[arg(long, global = true, value_parser = parse_gradient_by)]
+        pub gradient_by: Option<GradientBy>,
+
+        /// Interpolation of every layer: cardinal, centripetal, chordal, natural, natural-x,
+        /// b-spline, bezier or akima (each mode's own spline if omitted)
+        #[arg(long, global = true, value_parser = parse_interp)]
+        pub interp: Option<InterpKind>,
+
+        /// Tangent tension of cardinal and Catmull-Rom interpolation [default: 0.5]
+        #[arg(long, global = true)]
+        pub tension: Option<f64>,
+
+        /// Interpolated samples between control points, overriding the mode's own count
+        #[arg(long, global = true)]
+        pub segments: Option<u32>,
+
+        /// Resample every curve by arc length to one sample per this many pixels
+        #[arg(long, global = true)]
+        pub spacing: Option<f64>,
+
+        /// Background color as A,R,G,B in 0..1 (mode default if omitted)
+        #[arg(long, global = true, value_parser = parse_color, allow_hyphen_values = true)]
+        pub bg: Option<[f64; 4]>,
+
+        /// Sample splatting: point, bilinear, gaussian or gaussian:<sigma in pixels> [default: point]
+        #[arg(long, global = true, value_parser = parse_splat)]
+        pub splat: Option<Splat>,
+
+        /// Compositing: over, additive, screen, multiply, max, subtract or erase [default: over]
+        #[arg(long, global = true, value_parser = parse_blend)]
+        pub blend: Option<BlendMode>,
+
+        /// Accumulate in HDR and tone map: linear, log, gamma[:g] or exposure[:e] (direct compositing if omitted)
+        #[arg(long, global = true, value_parser = parse_tone)]
+        pub tone: Option<ToneMap>,
+
+        /// Worker threads for multi-spline layers such as circle [default: number of cores]
+        #[arg(long, global = true)]
+        pub threads: Option<usize>,
+
+        /// Seed for every generator, printed on each run so it can be replayed
+        #[arg(long, global = true, env = "SEED")]
+        pub seed: Option<u64>,
+
+        /// Close the window this many seconds after drawing finished, e.g. to browse random candidates
+        #[arg(long, global = true, value_parser = parse_seconds)]
+        pub timeout: Option<Duration>,
+
+        /// Stroke width in pixels of SVG output
+        #[arg(long, global = true, default_value_t = 1.0)]
+        pub stroke_width: f64,
+
+        /// Plotter page for G-code/HPGL output: a3, a4, a5, letter or <width>x<height> in mm
+        #[arg(long, global = true, value_parser = parse_page, default_value = "a4")]
+        pub page: Page,
+
+        /// Plotter page margin in mm
+        #[arg(long, global = true, default_value_t = 10.0)]
+        pub margin: f64,
+
+        /// G-code that lifts the pen
+        #[arg(long, global = true, default_value = "G0 Z5")]
+        pub pen_up: String,
+
+        /// G-code that lowers the pen
+        #[arg(long, global = true, default_value = "G0 Z0")]
+        pub pen_down: String,
+
+        /// G-code drawing speed in mm/min
+        #[arg(long, global = true, default_value_t = 3000.0, value_parser = parse_feed)]
+        pub feed: f64,
+
+        /// Save a PNG here instead of opening a window; *.svg saves the curves as SVG and
+        /// *.gcode, *.nc, *.hpgl or *.plt as pen plotter strokes (best with hieroglyphs and script)
+        #[arg(long, global = true, env = "OUTPUT")]
+        pub output: Option<String>,
+
+        /// Write the scene that was rendered (including its seed) to a .toml or .json file
+        #[arg(long, global = true)]
+        pub save_scene: Option<String>,
+    }
+
+    impl Cli {
+        /// The `--gradient` stops, if any were given.
+        pub fn gradient(&self) -> Option<Gradient> {
+            if self.gradient.is_empty() {
+                return None;
+            }
+            Some(Gradient { colors: self.gradient.clone(), by: self.gradient_by.unwrap_or_default() })
+        }
+    }
+
+    #[derive(Subcommand)]
+    pub enum Mode {
+        /// Concentric sand-spline circles, one thread per circle
+        Circle {
+            /// Number of circles
+            #[arg(short, long, env = "N", default_value_t = 3)]
+            n: usize,
+            #[command(flatten)]
+            opts: CircleSplineArgs,
+        },
+        /// Sand-spline evolution of a complex equation curve
+        Complex {
+            #[command(flatten)]
+            opts: CircleSplineArgs,
+            /// Equation term as A,B,C for exp(A + i*B*theta)*C, repeatable
+            #[arg(long = "param", value_parser = parse_param, allow_hyphen_values = true)]
+            params: Vec<[f64; 3]>,
+            #[command(flatten)]
+            outline: OutlineArgs,
+        },
+        /// Sand-spline evolution of a built-in curve: lissajous, rose, superformula, hypotrochoid,
+        /// epitrochoid, superellipse, lemniscate or spiral
+        Curve {
+            /// Curve family with optional parameters, e.g. rose:n=7,d=3 or hypotrochoid:fixed=7,rolling=4,pen=2
+            #[arg(default_value = "rose", value_parser = parse_curve)]
+            shape: CurveKind,
+            #[command(flatten)]
+            opts: CircleSplineArgs,
+            /// Width of the curve relative to the canvas side
+            #[arg(long, default_value_t = 0.8)]
+            size: f64,
+        },
+        /// Complex splines with random equation params, one window per candidate (Esc for the next, Q to stop)
+        Random {
+            /// Number of candidates
+            #[arg(long, default_value_t = 100)]
+            count: usize,
+            #[command(flatten)]
+            opts: CircleSplineArgs,
+            /// Render every candidate headlessly into this directory, with its scene file and a contact sheet
+            #[arg(long)]
+            gallery: Option<String>,
+            /// Contact sheet columns
+            #[arg(long, default_value_t = 10, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+            columns: usize,
+            /// Contact sheet thumbnail width in pixels
+            #[arg(long, default_value_t = 200, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+            thumb: usize,
+        },
+        /// Random cubic splines across the canvas
+        RandSplines,
+        /// A single complex equation curve, no evolution
+        ComplexCircle {
+            /// Equation term as A,B,C for exp(A + i*B*theta)*C, repeatable
+            #[arg(long = "param", value_parser = parse_param, allow_hyphen_values = true)]
+            params: Vec<[f64; 3]>,
+            #[command(flatten)]
+            outline: OutlineArgs,
+        },
+        /// Rows of spline handwriting
+        Script(ScriptArgs),
+        /// Grid of spline glyphs
+        Hieroglyphs(HieroglyphArgs),
+        /// Render a scene file (.toml or .json)
+        Scene {
+            file: String,
+        },
+    }
+
+    #[derive(Args)]
+    pub struct OutlineArgs {
+        /// Fit the equation to an outline instead: an .svg file or a text file of x,y points
+        #[arg(long, conflicts_with = "params")]
+        pub outline: Option<String>,
+        /// Fourier terms kept when fitting an outline
+        #[arg(long, default_value_t = 32, value_parser = clap::value_parser!(u32).range(1..))]
+        pub terms: u32,
+    }
+
+    #[derive(Args)]
+    pub struct CircleSplineArgs {
+        /// Noise step per control point
+        #[arg(long)]
+        pub stp: Option<f64>,
+        /// Interpolated segments per control point span
+        #[arg(long)]
+        pub inum: Option<u32>,
+        /// Lower bound (inclusive) of control point count
+        #[arg(long)]
+        pub pnum_from: Option<usize>,
+        /// Upper bound (exclusive) of control point count
+        #[arg(long)]
+        pub pnum_to: Option<usize>,
+        /// Evolution steps
+        #[arg(long)]
+        pub iterations: Option<usize>,
+        /// How control points move: random-walk, gaussian, perlin[:frequency], curl[:frequency]
+        /// or mean-reverting[:theta] [default: random-walk]
+        #[arg(long, value_parser = parse_noise)]
+        pub noise: Option<NoiseKind>,
+        /// Interpolate the spline as a closed loop, smooth where its ends meet
+        #[arg(long)]
+        pub closed: bool,
+        /// Sand grains scattered per curve segment instead of the curve points (try a low --inum)
+        #[arg(long)]
+        pub grains: Option<usize>,
+        /// Where grains fall on a segment: uniform, stratified or even [default: uniform]
+        #[arg(long, value_parser = parse_grain_distribution)]
+        pub grain_distribution: Option<GrainDistribution>,
+        /// Random grain offset radius as a fraction of the canvas side [default: 0]
+        #[arg(long)]
+        pub grain_jitter: Option<f64>,
+    }
+
+    impl CircleSplineArgs {
+        pub fn apply(&self, opts: &mut CircleSplineOpts) {
+            if let Some(v) = self.stp { opts.stp = v; }
+            if let Some(v) = self.inum { opts.inum = v; }
+            if let Some(v) = self.pnum_from { opts.pnum_from = v; }
+            if let Some(v) = self.pnum_to { opts.pnum_to = v; }
+            if let Some(v) = self.iterations { opts.iterations = v; }
+            if let Some(v) = self.noise { opts.noise = v; }
+            if self.closed { opts.closed = true; }
+            if self.grains.is_some() || self.grain_distribution.is_some() || self.grain_jitter.is_some() {
+                let grain = opts.grain.get_or_insert_with(GrainOpts::default);
+                if let Some(v) = self.grains { grain.per_segment = v; }
+                if let Some(v) = self.grain_distribution { grain.distribution = v; }
+                if let Some(v) = self.grain_jitter { grain.jitter = v; }
+            }
+        }
+    }
+
+    #[derive(Args)]
+    pub struct ScriptArgs {
+        /// Lines of handwriting
+        #[arg(long)]
+        pub row_count: Option<usize>,
+        /// Characters per line
+        #[arg(long)]
+        pub col_count: Option<usize>,
+        /// Margin around the text and between lines, as a fraction of the canvas side
+        #[arg(long)]
+        pub padding: Option<f64>,
+        /// Character width as a fraction of the canvas side
+        #[arg(long)]
+        pub char_width: Option<f64>,
+        /// Line height as a fraction of the canvas side
+        #[arg(long)]
+        pub char_height: Option<f64>,
+        /// Control points per character
+        #[arg(long)]
+        pub points: Option<usize>,
+    }
+
+    impl ScriptArgs {
+        pub fn apply(&self, opts: &mut ScriptOpts) {
+            if let Some(v) = self.row_count { opts.row_count = v; }
+            if let Some(v) = self.col_count { opts.col_count = v; }
+            if let Some(v) = self.padding { opts.padding = v; }
+            if let Some(v) = self.char_width { opts.char_width = v; }
+            if let Some(v) = self.char_height { opts.height = v; }
+            if let Some(v) = self.points { opts.points = v; }
+        }
+    }
+
+    #[derive(Args)]
+    pub struct HieroglyphArgs {
+        /// Rows of glyphs
+        #[arg(long)]
+        pub row_count: Option<usize>,
+        /// Glyphs per row
+        #[arg(long)]
+        pub col_count: Option<usize>,
+        /// Gap around and between glyphs, as a fraction of the canvas side
+        #[arg(long)]
+        pub padding: Option<f64>,
+        /// Glyph width as a fraction of the canvas side
+        #[arg(long)]
+        pub glyph_width: Option<f64>,
+        /// Glyph height as a fraction of the canvas side
+        #[arg(long)]
+        pub glyph_height: Option<f64>,
+        /// Control points per glyph
+        #[arg(long)]
+        pub points: Option<usize>,
+    }
+
+    impl HieroglyphArgs {
+        pub fn apply(&self, opts: &mut HieroglyphOpts) {
+            if let Some(v) = self.row_count { opts.row_count = v; }
+            if let Some(v) = self.col_count { opts.col_count = v; }
+            if let Some(v) = self.padding { opts.padding = v; }
+            if let Some(v) = self.glyph_width { opts.width = v; }
+            if let Some(v) = self.glyph_height { opts.height = v; }
+            if let Some(v) = self.points { opts.points = v; }
+        }
+    }
+
+    fn parse_floats(s: &str, n: usize) -> Result<Vec<f64>, String> {
+        let v = s.split(',')
+            .map(|p| p.trim().parse::< f64 >().map_err(|e| format!("{:?}: {}", p, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+        if v.len() != n {
+            return Err(format!("expected {} comma separated numbers, got {}", n, v.len()));
+        }
+        Ok(v)
+    }
+
+    fn parse_color(s: &str) -> Result<[f64; 4], String> {
+        let v = parse_floats(s, 4)?;
+        Ok([v[0], v[1], v[2], v[3]])
+    }
+
+    fn parse_gradient_by(s: &str) -> Result<GradientBy, String> {
+        match s {
+            "position" => Ok(GradientBy::Position),
+            "iteration" => Ok(GradientBy::Iteration),
+            "noise" => Ok(GradientBy::Noise),
+            _ => Err("expected position, iteration or noise".to_string()),
+        }
+    }
+
+    fn parse_interp(s: &str) -> Result<InterpKind, String> {
+        match s {
+            "cardinal" => Ok(InterpKind::Cardinal),
+            "centripetal" => Ok(InterpKind::Centripetal),
+            "chordal" => Ok(InterpKind::Chordal),
+            "natural" => Ok(InterpKind::Natural),
+            "natural-x" => Ok(InterpKind::NaturalX),
+            "b-spline" => Ok(InterpKind::BSpline),
+            "bezier" => Ok(InterpKind::Bezier),
+            "akima" => Ok(InterpKind::Akima),
+            _ => Err("expected cardinal, centripetal, chordal, natural, natural-x, b-spline, bezier or akima".to_string()),
+        }
+    }
+
+    fn parse_noise(s: &str) -> Result<NoiseKind, String> {
+        let (name, value) = match s.split_once(':') {
+            Some((n, v)) => (n, Some(v.parse::< f64 >().map_err(|e| format!("{:?}: {}", v, e))?)),
+            None => (s, None),
+        };
+        match name {
+            "random-walk" => Ok(NoiseKind::RandomWalk),
+            "gaussian" => Ok(NoiseKind::Gaussian),
+            "perlin" => Ok(NoiseKind::Perlin(value.unwrap_or(3.0))),
+            "curl" => Ok(NoiseKind::Curl(value.unwrap_or(3.0))),
+            "mean-reverting" => Ok(NoiseKind::MeanReverting(value.unwrap_or(0.1))),
+            _ => Err("expected random-walk, gaussian, perlin[:frequency], curl[:frequency] or mean-reverting[:theta]".to_string()),
+        }
+    }
+
+    // `name[:key=value,..]`, read like a `curve` table of a scene file
+    fn parse_curve(s: &str) -> Result<CurveKind, String> {
+        let (name, params) = s.split_once(':').unwrap_or((s, ""));
+        const NAMES: [&str; 8] = ["lissajous", "rose", "superformula", "hypotrochoid", "epitrochoid", "superellipse", "lemniscate", "spiral"];
+        if !NAMES.contains(&name.trim()) {
+            return Err(format!("expected {}, optionally followed by :<name>=<number>,..", NAMES.join(", ")));
+        }
+        let mut table = format!("kind = {:?}\n", name.trim());
+        for p in params.split(',').filter(|p| !p.trim().is_empty()) {
+            let (key, value) = p.split_once('=').ok_or_else(|| format!("{:?}: expected <name>=<number>", p))?;
+            value.trim().parse::< f64 >().map_err(|e| format!("{:?}: {}", value, e))?;
+            table.push_str(&format!("{} = {}\n", key.trim(), value.trim()));
+        }
+        toml::from_str(&table).map_err(|e| e.to_string())
+    }
+
+    fn parse_grain_distribution(s: &str) -> Result<GrainDistribution, String> {
+        match s {
+            "uniform" => Ok(GrainDistribution::Uniform),
+            "stratified" => Ok(GrainDistribution::Stratified),
+            "even" => Ok(GrainDistribution::Even),
+            _ => Err("expected uniform, stratified or even".to_string()),
+        }
+    }
+
+    fn parse_splat(s: &str) -> Result<Splat, String> {
+        match s {
+            "point" => Ok(Splat::Point),
+            "bilinear" => Ok(Splat::Bilinear),
+            "gaussian" => Ok(Splat::Gaussian(0.7)),
+            _ => match s.strip_prefix("gaussian:") {
+                Some(sigma) => sigma.parse::< f64 >().map(Splat::Gaussian).map_err(|e| format!("{:?}: {}", sigma, e)),
+                None => Err("expected point, bilinear, gaussian or gaussian:<sigma>".to_string()),
+            },
+        }
+    }
+
+    fn parse_blend(s: &str) -> Result<BlendMode, String> {
+        match s {
+            "over" => Ok(BlendMode::Over),
+            "additive" => Ok(BlendMode::Additive),
+            "screen" => Ok(BlendMode::Screen),
+            "multiply" => Ok(BlendMode::Multiply),
+            "max" => Ok(BlendMode::Max),
+            "subtract" => Ok(BlendMode::Subtract),
+            "erase" => Ok(BlendMode::Erase),
+            _ => Err("expected over, additive, screen, multiply, max, subtract or erase".to_string()),
+        }
+    }
+
+    fn parse_tone(s: &str) -> Result<ToneMap, String> {
+        let (name, value) = match s.split_once(':') {
+            Some((n, v)) => (n, Some(v.parse::< f64 >().map_err(|e| format!("{:?}: {}", v, e))?)),
+            None => (s, None),
+        };
+        match name {
+            "linear" => Ok(ToneMap::Linear),
+            "log" => Ok(ToneMap::Log),
+            "gamma" => Ok(ToneMap::Gamma(value.unwrap_or(2.2))),
+            "exposure" => Ok(ToneMap::Exposure(value.unwrap_or(1.0))),
+            _ => Err("expected linear, log, gamma[:g] or exposure[:e]".to_string()),
+        }
+    }
+
+    fn parse_seconds(s: &str) -> Result<Duration, String> {
+        let secs = s.parse::< f64 >().map_err(|e| format!("{:?}: {}", s, e))?;
+        Duration::try_from_secs_f64(secs).map_err(|_| format!("expected a number of seconds >= 0, got {}", s))
+    }
+
+    fn parse_page(s: &str) -> Result<Page, String> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "a3" => Page::A3,
+            "a4" => Page::A4,
+            "a5" => Page::A5,
+            "letter" => Page::LETTER,
+            other => match other.split_once('x') {
+                Some((w, h)) => Page {
+                    width: w.trim().parse::< f64 >().map_err(|e| format!("{:?}: {}", w, e))?,
+                    height: h.trim().parse::< f64 >().map_err(|e| format!("{:?}: {}", h, e))?,
+                    margin: 0.0,
+                },
+                None => return Err("expected a3, a4, a5, letter or <width>x<height> in mm".to_string()),
+            },
+        })
+    }
+
+    fn parse_feed(s: &str) -> Result<f64, String> {
+        let feed = s.parse::< f64 >().map_err(|e| format!("{:?}: {}", s, e))?;
+        if feed > 0.0 && feed.is_finite() {
+            Ok(feed)
+        } else {
+            Err(format!("expected a speed in mm/min > 0, got {}", s))
+        }
+    }
+
+    fn parse_param(s: &str) -> Result<[f64; 3], String> {
+        let v = parse_floats(s, 3)?;
+        Ok([v[0], v[1], v[2]])
+    }
+}
@@ -0,0 +1,384 @@
+pub mod config {
+    use std::{env, fmt};
+    use std::str::FromStr;
+
+    use crate::curve::curve::CurveKind;
+    use crate::interp::interp::Interpolation;
+    use crate::noise::noise::NoiseKind;
+    use crate::scene::scene::{Scene, Generator};
+    use crate::screen::screen::{Gradient, Splat, ToneMap};
+    use crate::spl::spl::{CircleSplineOpts, HieroglyphOpts, ScriptOpts};
+
+    /// Every environment variable the binary reads, shown in `--help` and on env errors.
+    pub const ENV_HELP: &str = "Environment variables:
+  STP=<float>          noise step per control point, > 0 (circle mode)
+  INUM=<int>           interpolated segments per control point span, >= 1 (circle mode)
+  PNUM_FROM=<int>      lower bound of control point count, >= 3 (circle mode)
+  PNUM_TO=<int>        upper bound (exclusive) of control point count, > PNUM_FROM (circle mode)
+  ITERATIONS=<int>     evolution steps, >= 1 (circle mode)
+  N=<int>              number of circles, same as --n
+  SEED=<int>           same as --seed
+  OUTPUT=<file>        same as --output (.png, .svg, .gcode or .hpgl)
+
+Command line flags take precedence over environment variables.";
+
+    #[derive(Debug)]
+    pub enum ConfigError {
+        /// An environment variable is set but does not parse
+        Env { var: &'static str, value: String, expected: &'static str },
+        /// A value parsed fine but is out of range or inconsistent with another one
+        Invalid { field: String, reason: String },
+    }
+
+    impl fmt::Display for ConfigError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                ConfigError::Env { var, value, expected } =>
+                    write!(f, "{}={:?} is not a valid value, expected {}", var, value, expected),
+                ConfigError::Invalid { field, reason } =>
+                    write!(f, "{}: {}", field, reason),
+            }
+        }
+    }
+
+    impl std::error::Error for ConfigError {}
+
+    fn invalid(field: &str, reason: String) -> ConfigError {
+        ConfigError::Invalid { field: field.to_string(), reason }
+    }
+
+    fn env_var<T: FromStr>(var: &'static str, expected: &'static str) -> Result<Option<T>, ConfigError> {
+        match env::var(var) {
+            Ok(v) => match v.trim().parse::< T >() {
+                Ok(t) => Ok(Some(t)),
+                Err(_) => Err(ConfigError::Env { var, value: v, expected }),
+            },
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Overrides `opts` with STP, INUM, PNUM_FROM, PNUM_TO and ITERATIONS when they are set.
+    /// Only parsing happens here; call `validate` once all overrides are applied.
+    pub fn from_env(opts: &mut CircleSplineOpts) -> Result<(), ConfigError> {
+        if let Some(v) = env_var("STP", "a number")? { opts.stp = v; }
+        if let Some(v) = env_var("INUM", "a positive integer")? { opts.inum = v; }
+        if let Some(v) = env_var("PNUM_FROM", "a positive integer")? { opts.pnum_from = v; }
+        if let Some(v) = env_var("PNUM_TO", "a positive integer")? { opts.pnum_to = v; }
+        if let Some(v) = env_var("ITERATIONS", "a positive integer")? { opts.iterations = v; }
+        Ok(())
+    }
+
+    /// Range and consistency checks, reported against the field path given in `at`
+    /// (e.g. `layers[1]`) so the user can find the offending value.
+    pub trait Validate {
+        fn validate(&self, at: &str) -> Result<(), ConfigError>;
+    }
+
+    fn path(at: &str, field: &str) -> String {
+        if at.is_empty() { field.to_string() } else { format!("{}.{}", at, field) }
+    }
+
+    fn positive(at: &str, field: &str, v: f64) -> Result<(), ConfigError> {
+        if !(v.is_finite() && v > 0.0) {
+            return Err(invalid(&path(at, field), format!("must be a finite number > 0, got {}", v)));
+        }
+        Ok(())
+    }
+
+    fn non_negative(at: &str, field: &str, v: f64) -> Result<(), ConfigError> {
+        if !(v.is_finite() && v >= 0.0) {
+            return Err(invalid(&path(at, field), format!("must be a finite number >= 0, got {}", v)));
+        }
+        Ok(())
+    }
+
+    fn finite(at: &str, field: &str, v: f64) -> Result<(), ConfigError> {
+        if !v.is_finite() {
+            return Err(invalid(&path(at, field), format!("must be a finite number, got {}", v)));
+        }
+        Ok(())
+    }
+
+    fn at_least(at: &str, field: &str, v: usize, min: usize) -> Result<(), ConfigError> {
+        if v < min {
+            return Err(invalid(&path(at, field), format!("must be at least {}, got {}", min, v)));
+        }
+        Ok(())
+    }
+
+    pub fn validate_canvas(at: &str, width: usize, height: usize) -> Result<(), ConfigError> {
+        at_least(at, "width", width, 1)?;
+        at_least(at, "height", height, 1)
+    }
+
+    pub fn validate_color(at: &str, field: &str, c: &[f64; 4]) -> Result<(), ConfigError> {
+        if c.iter().any(|v| !(0.0..=1.0).contains(v)) {
+            return Err(invalid(&path(at, field), format!("channels must be within 0..1, got {:?}", c)));
+        }
+        Ok(())
+    }
+
+    pub fn validate_gradient(at: &str, gradient: &Option<Gradient>) -> Result<(), ConfigError> {
+        if let Some(g) = gradient {
+            if g.colors.is_empty() {
+                return Err(invalid(&path(at, "gradient.colors"), "needs at least one color".to_string()));
+            }
+            for (i, c) in g.colors.iter().enumerate() {
+                validate_color(at, &format!("gradient.colors[{}]", i), c)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate_interpolation(at: &str, interp: &Option<Interpolation>) -> Result<(), ConfigError> {
+        if let Some(i) = interp {
+            non_negative(at, "interpolation.tension", i.tension)?;
+            if let Some(segments) = i.segments {
+                at_least(at, "interpolation.segments", segments as usize, 1)?;
+            }
+            if let Some(spacing) = i.spacing {
+                positive(at, "interpolation.spacing", spacing)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate_splat(at: &str, splat: &Splat) -> Result<(), ConfigError> {
+        if let Splat::Gaussian(sigma) = splat {
+            positive(at, "splat.gaussian", *sigma)?;
+        }
+        Ok(())
+    }
+
+    pub fn validate_tone(at: &str, tone: &Option<ToneMap>) -> Result<(), ConfigError> {
+        match tone {
+            Some(ToneMap::Gamma(g)) => positive(at, "tone.gamma", *g),
+            Some(ToneMap::Exposure(e)) => positive(at, "tone.exposure", *e),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn validate_params(at: &str, params: &[[f64; 3]], phases: &[f64]) -> Result<(), ConfigError> {
+        if params.is_empty() {
+            return Err(invalid(&path(at, "params"), "needs at least one [a, b, c] term".to_string()));
+        }
+        if params.iter().flatten().any(|v| !v.is_finite()) {
+            return Err(invalid(&path(at, "params"), "terms must be finite numbers".to_string()));
+        }
+        if !phases.is_empty() && phases.len() != params.len() {
+            return Err(invalid(&path(at, "phases"), format!("needs one phase per term ({}), got {}", params.len(), phases.len())));
+        }
+        if phases.iter().any(|v| !v.is_finite()) {
+            return Err(invalid(&path(at, "phases"), "phases must be finite numbers".to_string()));
+        }
+        Ok(())
+    }
+
+    impl Validate for CircleSplineOpts {
+        fn validate(&self, at: &str) -> Result<(), ConfigError> {
+            positive(at, "stp", self.stp)?;
+            at_least(at, "inum", self.inum as usize, 1)?;
+            at_least(at, "pnum_from", self.pnum_from, 3)?;
+            if self.pnum_to <= self.pnum_from {
+                return Err(invalid(&path(at, "pnum_to"),
+                    format!("must be greater than pnum_from ({}), got {}", self.pnum_from, self.pnum_to)));
+            }
+            at_least(at, "iterations", self.iterations, 1)?;
+            match self.noise {
+                NoiseKind::Perlin(f) => positive(at, "noise.perlin", f)?,
+                NoiseKind::Curl(f) => positive(at, "noise.curl", f)?,
+                NoiseKind::MeanReverting(theta) => if !(theta > 0.0 && theta <= 1.0) {
+                    return Err(invalid(&path(at, "noise.mean-reverting"), format!("must be within 0..1 (exclusive of 0), got {}", theta)));
+                },
+                NoiseKind::RandomWalk | NoiseKind::Gaussian => {},
+            }
+            if let Some(grain) = &self.grain {
+                at_least(at, "grain.per_segment", grain.per_segment, 1)?;
+                non_negative(at, "grain.jitter", grain.jitter)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl Validate for CurveKind {
+        fn validate(&self, at: &str) -> Result<(), ConfigError> {
+            let at = &path(at, "curve");
+            match self {
+                CurveKind::Lissajous(c) => {
+                    finite(at, "a", c.a)?;
+                    finite(at, "b", c.b)?;
+                    finite(at, "phase", c.phase)
+                },
+                CurveKind::Rose(c) => {
+                    at_least(at, "n", c.n as usize, 1)?;
+                    at_least(at, "d", c.d as usize, 1)
+                },
+                CurveKind::Superformula(c) => {
+                    finite(at, "m", c.m)?;
+                    if !(c.n1.is_finite() && c.n1 != 0.0) {
+                        return Err(invalid(&path(at, "n1"), format!("must be a finite number other than 0, got {}", c.n1)));
+                    }
+                    finite(at, "n2", c.n2)?;
+                    finite(at, "n3", c.n3)?;
+                    positive(at, "a", c.a)?;
+                    positive(at, "b", c.b)
+                },
+                CurveKind::Hypotrochoid(c) | CurveKind::Epitrochoid(c) => {
+                    at_least(at, "fixed", c.fixed as usize, 1)?;
+                    at_least(at, "rolling", c.rolling as usize, 1)?;
+                    finite(at, "pen", c.pen)
+                },
+                CurveKind::Superellipse(c) => {
+                    positive(at, "n", c.n)?;
+                    positive(at, "a", c.a)?;
+                    positive(at, "b", c.b)
+                },
+                CurveKind::Lemniscate => Ok(()),
+                CurveKind::Spiral(c) => {
+                    positive(at, "turns", c.turns)?;
+                    positive(at, "exponent", c.exponent)
+                },
+            }
+        }
+    }
+
+    impl Validate for ScriptOpts {
+        fn validate(&self, at: &str) -> Result<(), ConfigError> {
+            at_least(at, "row_count", self.row_count, 1)?;
+            at_least(at, "col_count", self.col_count, 1)?;
+            non_negative(at, "padding", self.padding)?;
+            positive(at, "char_width", self.char_width)?;
+            positive(at, "height", self.height)?;
+            // each character draws points-2..points+2 control points and a row needs two in total
+            at_least(at, "points", self.points, 3)?;
+            if self.col_count * (self.points - 2) < 2 {
+                return Err(invalid(&path(at, "points"),
+                    format!("a single-column row needs at least 4 points, got {}", self.points)));
+            }
+            Ok(())
+        }
+    }
+
+    impl Validate for HieroglyphOpts {
+        fn validate(&self, at: &str) -> Result<(), ConfigError> {
+            at_least(at, "row_count", self.row_count, 1)?;
+            at_least(at, "col_count", self.col_count, 1)?;
+            non_negative(at, "padding", self.padding)?;
+            positive(at, "width", self.width)?;
+            positive(at, "height", self.height)?;
+            at_least(at, "points", self.points, 2)
+        }
+    }
+
+    impl Validate for Scene {
+        fn validate(&self, at: &str) -> Result<(), ConfigError> {
+            validate_canvas(at, self.width, self.height)?;
+            validate_color(at, "bg", &self.bg)?;
+            validate_splat(at, &self.splat)?;
+            validate_tone(at, &self.tone)?;
+            if let Some(threads) = self.threads {
+                at_least(at, "threads", threads, 1)?;
+            }
+            if self.layers.is_empty() {
+                return Err(invalid(&path(at, "layers"), "needs at least one layer".to_string()));
+            }
+            for (i, layer) in self.layers.iter().enumerate() {
+                let at = path(at, &format!("layers[{}]", i));
+                validate_color(&at, "color", &layer.color)?;
+                validate_gradient(&at, &layer.gradient)?;
+                validate_interpolation(&at, &layer.interpolation)?;
+                match &layer.generator {
+                    Generator::Circle { count, opts } => {
+                        at_least(&at, "count", *count, 1)?;
+                        opts.validate(&at)?;
+                    },
+                    Generator::Complex { opts, params, phases } => {
+                        opts.validate(&at)?;
+                        validate_params(&at, params, phases)?;
+                    },
+                    Generator::ComplexCircle { params, phases } => validate_params(&at, params, phases)?,
+                    Generator::Curve { opts, curve, size } => {
+                        opts.validate(&at)?;
+                        curve.validate(&at)?;
+                        positive(&at, "size", *size)?;
+                    },
+                    Generator::RandSplines => {},
+                    Generator::Script(opts) => opts.validate(&at)?,
+                    Generator::Hieroglyphs(opts) => opts.validate(&at)?,
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn script(points: usize) -> ScriptOpts {
+            ScriptOpts { row_count: 12, col_count: 12, padding: 0.02, char_width: 0.08, height: 0.03, points }
+        }
+
+        fn hieroglyphs(points: usize) -> HieroglyphOpts {
+            HieroglyphOpts { row_count: 12, col_count: 12, padding: 0.02, width: 0.08, height: 0.03, points }
+        }
+
+        fn message<T: std::fmt::Debug>(r: Result<T, ConfigError>) -> String {
+            r.unwrap_err().to_string()
+        }
+
+        // the only test touching the environment, which every test thread shares
+        #[test]
+        fn parses_env_vars_and_leaves_ranges_to_validate() {
+            let mut opts = CircleSplineOpts::default();
+            env::set_var("STP", "fast");
+            let e = from_env(&mut opts);
+            env::remove_var("STP");
+            assert!(matches!(e, Err(ConfigError::Env { var: "STP", .. })));
+            assert_eq!(message(e), "STP=\"fast\" is not a valid value, expected a number");
+            assert!(ENV_HELP.contains("STP=<float>"));
+
+            env::set_var("ITERATIONS", "0");
+            let e = from_env(&mut opts);
+            env::remove_var("ITERATIONS");
+            e.unwrap();
+            assert_eq!(opts.iterations, 0);
+            assert_eq!(message(opts.validate("layers[0]")), "layers[0].iterations: must be at least 1, got 0");
+        }
+
+        #[test]
+        fn rejects_out_of_range_values() {
+            let opts = CircleSplineOpts { stp: -1.0, ..Default::default() };
+            assert_eq!(message(opts.validate("")), "stp: must be a finite number > 0, got -1");
+            let opts = CircleSplineOpts { pnum_from: 20, pnum_to: 20, ..Default::default() };
+            assert_eq!(message(opts.validate("")), "pnum_to: must be greater than pnum_from (20), got 20");
+            assert!(CircleSplineOpts::default().validate("").is_ok());
+        }
+
+        #[test]
+        fn rejects_too_few_glyph_points() {
+            assert_eq!(message(script(2).validate("")), "points: must be at least 3, got 2");
+            assert!(script(3).validate("").is_ok());
+            assert_eq!(message(hieroglyphs(1).validate("")), "points: must be at least 2, got 1");
+            assert!(hieroglyphs(2).validate("").is_ok());
+        }
+
+        #[test]
+        fn scene_validation_returns_the_error() {
+            let scene: Scene = toml::from_str(r#"
+                bg = [1.0, 0.0, 0.0, 0.0]
+
+                [[layers]]
+                generator = "script"
+                color = [1.0, 1.0, 1.0, 1.0]
+                row_count = 12
+                col_count = 12
+                padding = 0.02
+                char_width = 0.08
+                height = 0.03
+                points = 2
+            "#).unwrap();
+            assert_eq!(message(scene.validate("")), "layers[0].points: must be at least 3, got 2");
+        }
+    }
+}
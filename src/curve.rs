@@ -0,0 +1,243 @@
+pub mod curve {
+    use std::f64::consts::PI;
+    use ndarray::Array2;
+    use serde::{Deserialize, Serialize};
+
+    const TWOPI: f64 = 2.0 * PI;
+
+    /// A parametric shape to start a spline from.
+    pub trait Curve: Send + Sync {
+        /// Point at `t` in 0..1, in the curve's own units; closed curves end where they start.
+        fn at(&self, t: f64) -> [f64; 2];
+
+        /// `pnum` `[x, y]` rows evenly spread over `t`; a closed path leaves out `t = 1`, which
+        /// would repeat the first point (see `SplineBuilder::closed`).
+        fn path(&self, pnum: usize, closed: bool) -> Array2<f64> {
+            let n = if closed { pnum } else { pnum.saturating_sub(1) }.max(1) as f64;
+            let mut out = Array2::<f64>::zeros((pnum, 2));
+            for r in 0..pnum {
+                let p = self.at(r as f64 / n);
+                out[[r, 0]] = p[0];
+                out[[r, 1]] = p[1];
+            }
+            out
+        }
+    }
+
+    /// `path` scaled about the origin so its farthest point is `radius` away.
+    pub fn fit(path: Array2<f64>, radius: f64) -> Array2<f64> {
+        let max = path.rows().into_iter().fold(0.0f64, |m, r| m.max(r[0].hypot(r[1])));
+        if max > 0.0 { path * (radius / max) } else { path }
+    }
+
+    /// The built-in families, picked in scene files by `kind`, e.g.
+    /// `curve = { kind = "rose", n = 7, d = 3 }`; parameters left out take their defaults.
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "kind", rename_all = "kebab-case")]
+    pub enum CurveKind {
+        Lissajous(Lissajous),
+        Rose(Rose),
+        Superformula(Superformula),
+        Hypotrochoid(Trochoid),
+        Epitrochoid(Trochoid),
+        Superellipse(Superellipse),
+        Lemniscate,
+        Spiral(Spiral),
+    }
+
+    impl Default for CurveKind {
+        fn default() -> Self {
+            CurveKind::Rose(Rose::default())
+        }
+    }
+
+    impl Curve for CurveKind {
+        fn at(&self, t: f64) -> [f64; 2] {
+            match self {
+                CurveKind::Lissajous(c) => c.at(t),
+                CurveKind::Rose(c) => c.at(t),
+                CurveKind::Superformula(c) => c.at(t),
+                CurveKind::Hypotrochoid(c) => c.hypotrochoid(t),
+                CurveKind::Epitrochoid(c) => c.epitrochoid(t),
+                CurveKind::Superellipse(c) => c.at(t),
+                CurveKind::Lemniscate => Lemniscate.at(t),
+                CurveKind::Spiral(c) => c.at(t),
+            }
+        }
+    }
+
+    /// `(sin(a θ + phase), sin(b θ))`, closed for whole `a` and `b`
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct Lissajous {
+        pub a: f64,
+        pub b: f64,
+        /// In radians
+        pub phase: f64,
+    }
+
+    impl Default for Lissajous {
+        fn default() -> Self {
+            Lissajous { a: 3.0, b: 2.0, phase: PI / 2.0 }
+        }
+    }
+
+    impl Curve for Lissajous {
+        fn at(&self, t: f64) -> [f64; 2] {
+            let theta = TWOPI * t;
+            [(self.a * theta + self.phase).sin(), (self.b * theta).sin()]
+        }
+    }
+
+    /// Rhodonea `r = cos(n/d θ)`, traced until it closes
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct Rose {
+        pub n: u32,
+        pub d: u32,
+    }
+
+    impl Default for Rose {
+        fn default() -> Self {
+            Rose { n: 5, d: 1 }
+        }
+    }
+
+    impl Curve for Rose {
+        fn at(&self, t: f64) -> [f64; 2] {
+            let g = gcd(self.n, self.d).max(1);
+            let (n, d) = (self.n / g, self.d / g);
+            // odd n/d closes after d half turns, others after d full turns
+            let period = if n % 2 == 1 && d % 2 == 1 { PI } else { TWOPI } * d as f64;
+            let theta = period * t;
+            let r = (n as f64 / d as f64 * theta).cos();
+            [r * theta.cos(), r * theta.sin()]
+        }
+    }
+
+    /// Gielis' superformula, `r = (|cos(m θ / 4) / a|^n2 + |sin(m θ / 4) / b|^n3)^(-1 / n1)`
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct Superformula {
+        pub m: f64,
+        pub n1: f64,
+        pub n2: f64,
+        pub n3: f64,
+        pub a: f64,
+        pub b: f64,
+    }
+
+    impl Default for Superformula {
+        fn default() -> Self {
+            Superformula { m: 6.0, n1: 1.0, n2: 7.0, n3: 8.0, a: 1.0, b: 1.0 }
+        }
+    }
+
+    impl Curve for Superformula {
+        fn at(&self, t: f64) -> [f64; 2] {
+            let theta = TWOPI * t;
+            let q = self.m * theta / 4.0;
+            let s = (q.cos() / self.a).abs().powf(self.n2) + (q.sin() / self.b).abs().powf(self.n3);
+            let r = if s > 0.0 { s.powf(-1.0 / self.n1) } else { 0.0 };
+            [r * theta.cos(), r * theta.sin()]
+        }
+    }
+
+    /// A pen `pen` away from the center of a circle of radius `rolling` rolling inside
+    /// (hypotrochoid) or outside (epitrochoid) a fixed circle of radius `fixed`
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct Trochoid {
+        pub fixed: u32,
+        pub rolling: u32,
+        pub pen: f64,
+    }
+
+    impl Default for Trochoid {
+        fn default() -> Self {
+            Trochoid { fixed: 5, rolling: 3, pen: 5.0 }
+        }
+    }
+
+    impl Trochoid {
+        // the rolling circle is back where it started after this many turns around the fixed one
+        fn period(&self, t: f64) -> f64 {
+            TWOPI * (self.rolling / gcd(self.fixed, self.rolling).max(1)) as f64 * t
+        }
+
+        pub fn hypotrochoid(&self, t: f64) -> [f64; 2] {
+            let theta = self.period(t);
+            let (big, small) = (self.fixed as f64, self.rolling as f64);
+            let k = (big - small) / small;
+            [(big - small) * theta.cos() + self.pen * (k * theta).cos(), (big - small) * theta.sin() - self.pen * (k * theta).sin()]
+        }
+
+        pub fn epitrochoid(&self, t: f64) -> [f64; 2] {
+            let theta = self.period(t);
+            let (big, small) = (self.fixed as f64, self.rolling as f64);
+            let k = (big + small) / small;
+            [(big + small) * theta.cos() - self.pen * (k * theta).cos(), (big + small) * theta.sin() - self.pen * (k * theta).sin()]
+        }
+    }
+
+    /// Lamé curve `|x / a|^n + |y / b|^n = 1`: a rounded square for large `n`, a star below 1
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct Superellipse {
+        pub n: f64,
+        pub a: f64,
+        pub b: f64,
+    }
+
+    impl Default for Superellipse {
+        fn default() -> Self {
+            Superellipse { n: 4.0, a: 1.0, b: 1.0 }
+        }
+    }
+
+    impl Curve for Superellipse {
+        fn at(&self, t: f64) -> [f64; 2] {
+            let theta = TWOPI * t;
+            let e = 2.0 / self.n;
+            let (c, s) = (theta.cos(), theta.sin());
+            [self.a * c.signum() * c.abs().powf(e), self.b * s.signum() * s.abs().powf(e)]
+        }
+    }
+
+    /// Lemniscate of Bernoulli, a figure eight
+    pub struct Lemniscate;
+
+    impl Curve for Lemniscate {
+        fn at(&self, t: f64) -> [f64; 2] {
+            let theta = TWOPI * t;
+            let d = 1.0 + theta.sin().powi(2);
+            [theta.cos() / d, theta.sin() * theta.cos() / d]
+        }
+    }
+
+    /// `r = t^exponent` over `turns` turns: Archimedean for 1, Fermat's for 0.5; an open curve
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct Spiral {
+        pub turns: f64,
+        pub exponent: f64,
+    }
+
+    impl Default for Spiral {
+        fn default() -> Self {
+            Spiral { turns: 3.0, exponent: 1.0 }
+        }
+    }
+
+    impl Curve for Spiral {
+        fn at(&self, t: f64) -> [f64; 2] {
+            let theta = TWOPI * self.turns * t;
+            let r = t.powf(self.exponent);
+            [r * theta.cos(), r * theta.sin()]
+        }
+    }
+
+    fn gcd(a: u32, b: u32) -> u32 {
+        if b == 0 { a } else { gcd(b, a % b) }
+    }
+}
@@ -0,0 +1,524 @@
+pub mod fourier {
+    use std::f64::consts::PI;
+    use std::{fmt, fs, io};
+    use std::path::Path;
+    use ndarray::Array2;
+    use num::Complex;
+
+    use crate::interp::interp::resample;
+    use crate::spl::spl::ComplexEquationParams;
+
+    const TWOPI: f64 = 2.0 * PI;
+
+    // outline points the coefficients are computed from
+    const SAMPLES: usize = 1024;
+
+    // line segments per SVG curve command, before the outline is resampled
+    const CURVE_STEPS: usize = 16;
+
+    #[derive(Debug)]
+    pub enum OutlineError {
+        Io(io::Error),
+        Parse(String),
+    }
+
+    impl fmt::Display for OutlineError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                OutlineError::Io(e) => write!(f, "{}", e),
+                OutlineError::Parse(e) => write!(f, "invalid outline: {}", e),
+            }
+        }
+    }
+
+    impl std::error::Error for OutlineError {}
+
+    /// Reads an outline as `[x, y]` points, y pointing down like on the canvas. `*.svg` files give
+    /// every `<path>`, `<polygon>` and `<polyline>` one after another (transforms are ignored);
+    /// anything else holds one `x y` or `x,y` point per line, `#` starting a comment.
+    pub fn load(path: &str) -> Result<Vec<[f64; 2]>, OutlineError> {
+        let text = fs::read_to_string(path).map_err(OutlineError::Io)?;
+        let is_svg = Path::new(path).extension().is_some_and(|e| e.eq_ignore_ascii_case("svg"));
+        let points = if is_svg { svg_points(&text)? } else { text_points(&text)? };
+        if points.len() < 3 {
+            return Err(OutlineError::Parse(format!("needs at least 3 points, got {}", points.len())));
+        }
+        Ok(points)
+    }
+
+    /// The `terms` largest Fourier terms of the closed outline through `points`, centered and
+    /// scaled to fit a circle of radius 0.5 like the default `complex` params.
+    pub fn fit(points: &[[f64; 2]], terms: usize) -> ComplexEquationParams {
+        let z = normalize(&even(points));
+        let n = z.len() as i64;
+
+        let mut coefficients: Vec<(i64, Complex<f64>)> = (-n / 2..n - n / 2).map(|k| {
+            let c = z.iter().enumerate().fold(Complex::new(0.0, 0.0), |sum, (j, p)| {
+                sum + p * Complex::from_polar(1.0, -TWOPI * (k * j as i64) as f64 / n as f64)
+            });
+            (k, c / n as f64)
+        }).collect();
+        coefficients.sort_by(|a, b| b.1.norm().total_cmp(&a.1.norm()));
+        coefficients.truncate(terms.max(1));
+
+        ComplexEquationParams {
+            params: coefficients.iter().map(|(k, c)| [0.0, *k as f64, c.norm()]).collect(),
+            phases: coefficients.iter().map(|(_, c)| c.arg()).collect(),
+        }
+    }
+
+    // SAMPLES points evenly spaced along the closed outline
+    fn even(points: &[[f64; 2]]) -> Vec<[f64; 2]> {
+        let mut closed = points.to_vec();
+        closed.push(points[0]);
+        let xy = Array2::from_shape_fn((closed.len(), 2), |(r, c)| closed[r][c]);
+        let total: f64 = closed.windows(2).map(|w| (w[1][0] - w[0][0]).hypot(w[1][1] - w[0][1])).sum();
+        let mut even = resample(&xy, total / SAMPLES as f64);
+        // the last row repeats the first
+        if even.nrows() > 1 {
+            even = even.slice(ndarray::s![..even.nrows() - 1, ..]).to_owned();
+        }
+        even.rows().into_iter().map(|r| [r[0], r[1]]).collect()
+    }
+
+    // centered on the bounding box, the farthest point 0.5 away
+    fn normalize(points: &[[f64; 2]]) -> Vec<Complex<f64>> {
+        let (mut min, mut max) = ([f64::MAX; 2], [f64::MIN; 2]);
+        for p in points {
+            for c in 0..2 {
+                min[c] = min[c].min(p[c]);
+                max[c] = max[c].max(p[c]);
+            }
+        }
+        let center = Complex::new((min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0);
+        let z: Vec<Complex<f64>> = points.iter().map(|p| Complex::new(p[0], p[1]) - center).collect();
+        let radius = z.iter().fold(0.0f64, |m, p| m.max(p.norm()));
+        if radius > 0.0 { z.iter().map(|p| p * (0.5 / radius)).collect() } else { z }
+    }
+
+    fn text_points(text: &str) -> Result<Vec<[f64; 2]>, OutlineError> {
+        let mut points = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let v = line.split(|c: char| c == ',' || c.is_whitespace()).filter(|s| !s.is_empty())
+                .map(|s| s.parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|e| OutlineError::Parse(format!("line {}: {}", i + 1, e)))?;
+            if v.len() != 2 {
+                return Err(OutlineError::Parse(format!("line {}: expected x and y, got {} numbers", i + 1, v.len())));
+            }
+            points.push([v[0], v[1]]);
+        }
+        Ok(points)
+    }
+
+    fn svg_points(text: &str) -> Result<Vec<[f64; 2]>, OutlineError> {
+        let mut points = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find('<') {
+            rest = &rest[start + 1..];
+            let end = rest.find('>').unwrap_or(rest.len());
+            let tag = &rest[..end];
+            let name = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("");
+            match name {
+                "path" => if let Some(d) = attribute(tag, "d") {
+                    points.extend(path_points(d)?);
+                },
+                "polygon" | "polyline" => if let Some(p) = attribute(tag, "points") {
+                    let v = numbers(p)?;
+                    points.extend(v.chunks_exact(2).map(|c| [c[0], c[1]]));
+                },
+                _ => {},
+            }
+            rest = &rest[end..];
+        }
+        Ok(points)
+    }
+
+    // value of ` name="..."` (or single quoted) in the text of a tag
+    fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+        let mut from = 0;
+        while let Some(i) = tag[from..].find(name) {
+            let at = from + i;
+            from = at + name.len();
+            let before = tag[..at].chars().last();
+            if !before.is_some_and(|c| c.is_whitespace()) {
+                continue;
+            }
+            let after = tag[from..].trim_start();
+            if let Some(value) = after.strip_prefix('=') {
+                let value = value.trim_start();
+                let quote = value.chars().next()?;
+                if quote != '"' && quote != '\'' {
+                    return None;
+                }
+                let value = &value[1..];
+                return value.find(quote).map(|e| &value[..e]);
+            }
+        }
+        None
+    }
+
+    fn numbers(s: &str) -> Result<Vec<f64>, OutlineError> {
+        let mut tokens = Tokens { s: s.as_bytes(), i: 0 };
+        let mut v = Vec::new();
+        while let Some(n) = tokens.number()? {
+            v.push(n);
+        }
+        Ok(v)
+    }
+
+    fn missing_numbers() -> OutlineError {
+        OutlineError::Parse("path command is missing numbers".to_string())
+    }
+
+    struct Tokens<'a> {
+        s: &'a [u8],
+        i: usize,
+    }
+
+    impl<'a> Tokens<'a> {
+        fn skip_separators(&mut self) {
+            while self.i < self.s.len() && (self.s[self.i].is_ascii_whitespace() || self.s[self.i] == b',') {
+                self.i += 1;
+            }
+        }
+
+        fn command(&mut self) -> Option<u8> {
+            self.skip_separators();
+            let c = *self.s.get(self.i)?;
+            if c.is_ascii_alphabetic() && c != b'e' && c != b'E' {
+                self.i += 1;
+                Some(c)
+            } else {
+                None
+            }
+        }
+
+        // the next number, or None at a command letter or the end; "1.5.5" is two numbers
+        fn number(&mut self) -> Result<Option<f64>, OutlineError> {
+            self.skip_separators();
+            let start = self.i;
+            let s = self.s;
+            let digits = |i: &mut usize| while *i < s.len() && s[*i].is_ascii_digit() { *i += 1 };
+            if self.i < s.len() && (s[self.i] == b'+' || s[self.i] == b'-') {
+                self.i += 1;
+            }
+            digits(&mut self.i);
+            if self.i < s.len() && s[self.i] == b'.' {
+                self.i += 1;
+                digits(&mut self.i);
+            }
+            if self.i > start && self.i < s.len() && (s[self.i] == b'e' || s[self.i] == b'E') {
+                let mark = self.i;
+                self.i += 1;
+                if self.i < s.len() && (s[self.i] == b'+' || s[self.i] == b'-') {
+                    self.i += 1;
+                }
+                let exponent = self.i;
+                digits(&mut self.i);
+                if self.i == exponent {
+                    self.i = mark;
+                }
+            }
+            if self.i == start {
+                return if start < s.len() && !s[start].is_ascii_alphabetic() {
+                    Err(OutlineError::Parse(format!("unexpected {:?} in path data", s[start] as char)))
+                } else {
+                    Ok(None)
+                };
+            }
+            let text = std::str::from_utf8(&s[start..self.i]).unwrap();
+            text.parse::<f64>().map(Some).map_err(|e| OutlineError::Parse(format!("{:?}: {}", text, e)))
+        }
+
+        // an arc flag: a single 0 or 1, which needs no separator after it as in "a1 1 0 011 1"
+        fn flag(&mut self) -> Result<bool, OutlineError> {
+            self.skip_separators();
+            match self.s.get(self.i) {
+                Some(b'0') => { self.i += 1; Ok(false) },
+                Some(b'1') => { self.i += 1; Ok(true) },
+                Some(c) => Err(OutlineError::Parse(format!("expected an arc flag, got {:?}", *c as char))),
+                None => Err(missing_numbers()),
+            }
+        }
+
+        // the unparsed text up to the next separator, if any is left
+        fn rest(&mut self) -> Option<&'a str> {
+            self.skip_separators();
+            let s = self.s;
+            let end = s[self.i..].iter().position(|c| c.is_ascii_whitespace() || *c == b',').map_or(s.len(), |n| self.i + n);
+            if self.i < end { std::str::from_utf8(&s[self.i..end]).ok() } else { None }
+        }
+
+        fn numbers<const N: usize>(&mut self) -> Result<Option<[f64; N]>, OutlineError> {
+            let mut v = [0.0; N];
+            for (k, n) in v.iter_mut().enumerate() {
+                match self.number()? {
+                    Some(x) => *n = x,
+                    None if k == 0 => return Ok(None),
+                    None => return Err(missing_numbers()),
+                }
+            }
+            Ok(Some(v))
+        }
+    }
+
+    // every subpath of SVG path data as line segments, curves and arcs flattened
+    fn path_points(d: &str) -> Result<Vec<[f64; 2]>, OutlineError> {
+        let mut tokens = Tokens { s: d.as_bytes(), i: 0 };
+        let mut out: Vec<[f64; 2]> = Vec::new();
+        let (mut pos, mut start) = ([0.0, 0.0], [0.0, 0.0]);
+        // the control point a following S or T mirrors
+        let mut last_control: Option<(u8, [f64; 2])> = None;
+        while let Some(cmd) = tokens.command() {
+            let relative = cmd.is_ascii_lowercase();
+            let base = |p: [f64; 2], pos: [f64; 2]| if relative { [p[0] + pos[0], p[1] + pos[1]] } else { p };
+            let upper = cmd.to_ascii_uppercase();
+            let mut first = true;
+            loop {
+                let mut control = None;
+                match upper {
+                    b'M' | b'L' | b'T' => {
+                        let Some([x, y]) = tokens.numbers::<2>()? else { break };
+                        let p = base([x, y], pos);
+                        if upper == b'M' && first {
+                            start = p;
+                        } else if upper == b'T' {
+                            let c = reflect(last_control, b'Q', pos);
+                            out.extend(quadratic(pos, c, p));
+                            control = Some((b'Q', c));
+                        }
+                        out.push(p);
+                        pos = p;
+                    },
+                    b'H' => {
+                        let Some([x]) = tokens.numbers::<1>()? else { break };
+                        pos = [if relative { pos[0] + x } else { x }, pos[1]];
+                        out.push(pos);
+                    },
+                    b'V' => {
+                        let Some([y]) = tokens.numbers::<1>()? else { break };
+                        pos = [pos[0], if relative { pos[1] + y } else { y }];
+                        out.push(pos);
+                    },
+                    b'C' => {
+                        let Some([x1, y1, x2, y2, x, y]) = tokens.numbers::<6>()? else { break };
+                        let (c1, c2, p) = (base([x1, y1], pos), base([x2, y2], pos), base([x, y], pos));
+                        out.extend(cubic(pos, c1, c2, p));
+                        control = Some((b'C', c2));
+                        out.push(p);
+                        pos = p;
+                    },
+                    b'S' => {
+                        let Some([x2, y2, x, y]) = tokens.numbers::<4>()? else { break };
+                        let (c2, p) = (base([x2, y2], pos), base([x, y], pos));
+                        out.extend(cubic(pos, reflect(last_control, b'C', pos), c2, p));
+                        control = Some((b'C', c2));
+                        out.push(p);
+                        pos = p;
+                    },
+                    b'Q' => {
+                        let Some([x1, y1, x, y]) = tokens.numbers::<4>()? else { break };
+                        let (c, p) = (base([x1, y1], pos), base([x, y], pos));
+                        out.extend(quadratic(pos, c, p));
+                        control = Some((b'Q', c));
+                        out.push(p);
+                        pos = p;
+                    },
+                    b'A' => {
+                        let Some([rx, ry, angle]) = tokens.numbers::<3>()? else { break };
+                        let (large, sweep) = (tokens.flag()?, tokens.flag()?);
+                        let [x, y] = tokens.numbers::<2>()?.ok_or_else(missing_numbers)?;
+                        let p = base([x, y], pos);
+                        out.extend(arc(pos, rx, ry, angle, large, sweep, p));
+                        out.push(p);
+                        pos = p;
+                    },
+                    b'Z' => {
+                        out.push(start);
+                        pos = start;
+                    },
+                    _ => return Err(OutlineError::Parse(format!("unknown path command {:?}", cmd as char))),
+                }
+                last_control = control;
+                first = false;
+                if upper == b'Z' {
+                    break;
+                }
+            }
+            if first && upper != b'Z' {
+                return Err(OutlineError::Parse(format!("path command {:?} without numbers", cmd as char)));
+            }
+        }
+        // anything left is neither a command nor a number, e.g. the 5 in "M0 0 L1 0 Z 5"
+        if let Some(token) = tokens.rest() {
+            return Err(OutlineError::Parse(format!("unexpected {:?} in path data", token)));
+        }
+        Ok(out)
+    }
+
+    // control point mirrored around `pos` when the previous command was of the same kind
+    fn reflect(last: Option<(u8, [f64; 2])>, kind: u8, pos: [f64; 2]) -> [f64; 2] {
+        match last {
+            Some((k, c)) if k == kind => [2.0 * pos[0] - c[0], 2.0 * pos[1] - c[1]],
+            _ => pos,
+        }
+    }
+
+    // points after `p0` up to, not including, `p3`
+    fn cubic(p0: [f64; 2], p1: [f64; 2], p2: [f64; 2], p3: [f64; 2]) -> Vec<[f64; 2]> {
+        (1..CURVE_STEPS).map(|i| {
+            let t = i as f64 / CURVE_STEPS as f64;
+            let u = 1.0 - t;
+            let w = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+            [0, 1].map(|c| w[0] * p0[c] + w[1] * p1[c] + w[2] * p2[c] + w[3] * p3[c])
+        }).collect()
+    }
+
+    fn quadratic(p0: [f64; 2], p1: [f64; 2], p2: [f64; 2]) -> Vec<[f64; 2]> {
+        (1..CURVE_STEPS).map(|i| {
+            let t = i as f64 / CURVE_STEPS as f64;
+            let u = 1.0 - t;
+            [0, 1].map(|c| u * u * p0[c] + 2.0 * u * t * p1[c] + t * t * p2[c])
+        }).collect()
+    }
+
+    // elliptical arc from endpoint to center parameters, SVG 1.1 appendix F.6.5
+    fn arc(p0: [f64; 2], rx: f64, ry: f64, angle: f64, large: bool, sweep: bool, p: [f64; 2]) -> Vec<[f64; 2]> {
+        let (mut rx, mut ry) = (rx.abs(), ry.abs());
+        if rx == 0.0 || ry == 0.0 || p0 == p {
+            return Vec::new();
+        }
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let (dx, dy) = ((p0[0] - p[0]) / 2.0, (p0[1] - p[1]) / 2.0);
+        let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+        let lambda = (x1 / rx).powi(2) + (y1 / ry).powi(2);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let num = (rx * ry).powi(2) - (rx * y1).powi(2) - (ry * x1).powi(2);
+        let den = (rx * y1).powi(2) + (ry * x1).powi(2);
+        let mut k = (num / den).max(0.0).sqrt();
+        if large == sweep {
+            k = -k;
+        }
+        let (cx1, cy1) = (k * rx * y1 / ry, -k * ry * x1 / rx);
+        let (cx, cy) = (cos * cx1 - sin * cy1 + (p0[0] + p[0]) / 2.0, sin * cx1 + cos * cy1 + (p0[1] + p[1]) / 2.0);
+
+        let start = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+        let mut delta = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx) - start;
+        if sweep && delta < 0.0 {
+            delta += TWOPI;
+        } else if !sweep && delta > 0.0 {
+            delta -= TWOPI;
+        }
+        (1..CURVE_STEPS).map(|i| {
+            let a = start + delta * i as f64 / CURVE_STEPS as f64;
+            let (x, y) = (rx * a.cos(), ry * a.sin());
+            [cos * x - sin * y + cx, sin * x + cos * y + cy]
+        }).collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::curve::curve::Curve;
+
+        fn near(a: [f64; 2], b: [f64; 2], tolerance: f64) -> bool {
+            (a[0] - b[0]).hypot(a[1] - b[1]) < tolerance
+        }
+
+        fn same_path(a: &str, b: &str) {
+            let (pa, pb) = (path_points(a).unwrap(), path_points(b).unwrap());
+            assert_eq!(pa.len(), pb.len(), "{:?} vs {:?}", a, b);
+            for (p, q) in pa.iter().zip(pb.iter()) {
+                assert!(near(*p, *q, 1e-12), "{:?} vs {:?}: {:?} vs {:?}", a, b, p, q);
+            }
+        }
+
+        #[test]
+        fn fits_a_circle_with_one_term() {
+            let points: Vec<[f64; 2]> = (0..64).map(|i| {
+                let a = TWOPI * i as f64 / 64.0;
+                [5.0 + 3.0 * a.cos(), 5.0 + 3.0 * a.sin()]
+            }).collect();
+            let cc = fit(&points, 3);
+            assert_eq!(cc.params[0][1], 1.0);
+            assert!((cc.params[0][2] - 0.5).abs() < 1e-3);
+            for k in 0..16 {
+                let p = cc.at(k as f64 / 16.0);
+                assert!((p[0].hypot(p[1]) - 0.5).abs() < 1e-3, "{:?}", p);
+            }
+        }
+
+        #[test]
+        fn reconstructs_a_square() {
+            let cc = fit(&path_points("M0 0 L1 0 L1 1 L0 1 Z").unwrap(), 64);
+            // centered on (0.5, 0.5) with the corners 0.5 away, starting at (0, 0)
+            let s = 0.5 / 0.5f64.hypot(0.5);
+            let expected = [[0.0, -0.5 * s], [0.5 * s, 0.0], [0.0, 0.5 * s], [-0.5 * s, 0.0]];
+            for (k, e) in expected.iter().enumerate() {
+                let p = cc.at((2 * k + 1) as f64 / 8.0);
+                assert!(near(p, *e, 0.01), "{:?} vs {:?}", p, e);
+            }
+        }
+
+        #[test]
+        fn relative_commands_match_absolute_ones() {
+            same_path("m1 1 l2 0 c1 0 1 1 0 1 h-1 v-1 z", "M1 1 L3 1 C4 1 4 2 3 2 H2 V1 Z");
+            same_path("M1 1 q1 0 1 1 t1 1 s1 1 1 0", "M1 1 Q2 1 2 2 T3 3 S4 4 4 3");
+        }
+
+        #[test]
+        fn curves_end_on_their_end_points() {
+            for d in ["M0 0 C0 1 1 1 2 0", "M0 0 S1 1 2 0", "M0 0 Q1 1 2 0", "M0 0 T2 0", "M0 0 A1 1 0 0 0 2 0"] {
+                let points = path_points(d).unwrap();
+                assert_eq!(points.len(), CURVE_STEPS + 1, "{:?}", d);
+                assert_eq!(*points.last().unwrap(), [2.0, 0.0], "{:?}", d);
+            }
+        }
+
+        #[test]
+        fn repeats_commands_for_extra_coordinates() {
+            same_path("M0 0 1 0 1 1", "M0 0 L1 0 L1 1");
+            same_path("m0 0 1 0 0 1", "M0 0 L1 0 L1 1");
+            same_path("M0 0 L1 0 2 0 H3 4", "M0 0 L1 0 L2 0 H3 H4");
+        }
+
+        #[test]
+        fn tokenizes_numbers_without_separators() {
+            assert_eq!(numbers("1.5.5-2e1,3 .25").unwrap(), vec![1.5, 0.5, -20.0, 3.0, 0.25]);
+        }
+
+        #[test]
+        fn flattens_arcs() {
+            let points = path_points("M0 0 A1 1 0 0 1 2 0").unwrap();
+            assert_eq!(*points.last().unwrap(), [2.0, 0.0]);
+            // a half circle about (1, 0)
+            for p in points.iter() {
+                assert!(((p[0] - 1.0).hypot(p[1]) - 1.0).abs() < 1e-9, "{:?}", p);
+            }
+            assert!(points.iter().any(|p| (p[1].abs() - 1.0).abs() < 0.05));
+        }
+
+        #[test]
+        fn reads_compact_arc_flags() {
+            same_path("M0 0 a1 1 0 011 1", "M0 0 a1 1 0 0 1 1 1");
+            same_path("M0 0 A1 1 0 1 0 2 0", "M0 0 A1,1,0,1,0,2,0");
+            assert!(path_points("M0 0 a1 1 0 2 1 1 1").is_err());
+        }
+
+        #[test]
+        fn rejects_leftover_tokens() {
+            match path_points("M0 0 L1 0 Z 5") {
+                Err(OutlineError::Parse(e)) => assert!(e.contains("\"5\""), "{}", e),
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+    }
+}
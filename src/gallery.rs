@@ -0,0 +1,86 @@
+pub mod gallery {
+    use std::{fmt, fs, io};
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    use crate::job::job;
+    use crate::scene::scene::{self, Scene, SceneError};
+    use crate::screen::screen::{self, Screen, set_bg};
+
+    pub struct GalleryOpts {
+        pub dir: String,
+        pub columns: usize,
+        /// Thumbnail width in pixels, the height keeps the canvas aspect ratio
+        pub thumb: usize,
+        /// Candidates drawn at the same time
+        pub threads: usize,
+    }
+
+    #[derive(Debug)]
+    pub enum GalleryError {
+        Io(io::Error),
+        Png(png::EncodingError),
+        Scene(SceneError),
+    }
+
+    impl fmt::Display for GalleryError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                GalleryError::Io(e) => write!(f, "{}", e),
+                GalleryError::Png(e) => write!(f, "{}", e),
+                GalleryError::Scene(e) => write!(f, "{}", e),
+            }
+        }
+    }
+
+    impl std::error::Error for GalleryError {}
+
+    // thumbnails are laid out on a dark sheet with this gap around them
+    const GAP: usize = 8;
+
+    fn candidate_path(dir: &str, i: usize, ext: &str) -> String {
+        Path::new(dir).join(format!("candidate-{:03}.{}", i, ext)).to_string_lossy().into_owned()
+    }
+
+    /// Renders every scene headlessly into `opts.dir`: `candidate-NNN.png` next to the scene that
+    /// reproduces it (seed and parameters) in `candidate-NNN.toml`, then `contact-sheet.png` with
+    /// all thumbnails row by row in candidate order.
+    pub fn render(scenes: &[Scene], opts: &GalleryOpts) -> Result<(), GalleryError> {
+        fs::create_dir_all(&opts.dir).map_err(GalleryError::Io)?;
+
+        let mut thumbs = Vec::<Screen>::with_capacity(scenes.len());
+        for (c, chunk) in scenes.chunks(opts.threads.max(1)).enumerate() {
+            let jobs: Vec<_> = chunk.iter().map(job::start).collect();
+            for (k, (j, scene)) in jobs.into_iter().zip(chunk.iter()).enumerate() {
+                let i = c * opts.threads.max(1) + k;
+                let sm = j.finish();
+                let mut s = sm.lock().unwrap();
+
+                let png = candidate_path(&opts.dir, i, "png");
+                s.save_png(&png).map_err(GalleryError::Png)?;
+                scene::save(scene, &candidate_path(&opts.dir, i, "toml")).map_err(GalleryError::Scene)?;
+                println!("saved {}", png);
+
+                let h = (opts.thumb * s.height() / s.width()).max(1);
+                thumbs.push(s.thumbnail(opts.thumb, h));
+            }
+        }
+
+        if let Some(first) = thumbs.first() {
+            let (tw, th) = (first.width(), first.height());
+            let columns = opts.columns.clamp(1, thumbs.len());
+            let rows = thumbs.len().div_ceil(columns);
+            let sheet = Arc::new(Mutex::new(screen::new(columns * (tw + GAP) + GAP, rows * (th + GAP) + GAP)));
+            set_bg(sheet.clone(), [1.0, 0.1, 0.1, 0.1]);
+
+            let mut s = sheet.lock().unwrap();
+            for (i, t) in thumbs.iter().enumerate() {
+                s.paste(t, GAP + (i % columns) * (tw + GAP), GAP + (i / columns) * (th + GAP));
+            }
+            let path = Path::new(&opts.dir).join("contact-sheet.png").to_string_lossy().into_owned();
+            s.save_png(&path).map_err(GalleryError::Png)?;
+            println!("saved {}", path);
+        }
+        Ok(())
+    }
+}
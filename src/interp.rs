@@ -0,0 +1,540 @@
+pub mod interp {
+    use ndarray::{Array2, ArrayView1};
+    use serde::{Deserialize, Serialize};
+
+    /// Curve through (or for `b-spline` and `bezier`, guided by) control points.
+    pub trait Interpolator: Send + Sync {
+        /// Samples `segments` rows per span between the `[x, y]` control points `xy`, followed by
+        /// the end point: the last control point of an open curve, the first of a closed one,
+        /// which also gets a span from its last point back to the first.
+        fn interpolate(&self, xy: &Array2<f64>, segments: u32, closed: bool) -> Array2<f64>;
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum InterpKind {
+        /// Cardinal spline with uniform parameters, the original interpolation
+        #[default]
+        Cardinal,
+        /// Catmull-Rom with centripetal parameters: no cusps or self-intersections within a span
+        Centripetal,
+        /// Catmull-Rom with chord length parameters: rounder, wider arcs
+        Chordal,
+        /// Natural cubic spline, continuous in curvature
+        Natural,
+        /// Natural cubic y(x) through the points sorted by x: a graph across the canvas rather
+        /// than a curve, the original rand-splines shape
+        NaturalX,
+        /// Uniform cubic B-spline, smoothest; approximates the inner control points
+        BSpline,
+        /// A single Bézier curve over all control points, a smooth average of their shape
+        Bezier,
+        /// Akima spline, overshoots less around sudden changes
+        Akima,
+    }
+
+    /// The interpolation of a layer, see `InterpKind`.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Interpolation {
+        /// The generator's own spline when missing: cardinal, or natural-x for rand-splines
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub kind: Option<InterpKind>,
+        /// Tangent scale of cardinal and Catmull-Rom splines, 0.5 being the classic Catmull-Rom
+        #[serde(default = "default_tension")]
+        pub tension: f64,
+        /// Samples per span; the generator's own count (e.g. `inum`) when missing
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub segments: Option<u32>,
+        /// Resample every curve by arc length to one sample per this many pixels, so long and
+        /// short spans get the same density; `segments` then only sets how finely it is traced
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub spacing: Option<f64>,
+        // pixels per unit of the path coordinates, see `on_canvas`
+        #[serde(skip)]
+        side: Option<f64>,
+    }
+
+    fn default_tension() -> f64 {
+        0.5
+    }
+
+    impl Default for Interpolation {
+        fn default() -> Self {
+            Interpolation { kind: None, tension: default_tension(), segments: None, spacing: None, side: None }
+        }
+    }
+
+    impl Interpolation {
+        pub fn interpolator(&self) -> Box<dyn Interpolator> {
+            match self.kind.unwrap_or_default() {
+                InterpKind::Cardinal => Box::new(Cardinal { tension: self.tension }),
+                InterpKind::Centripetal => Box::new(CatmullRom { alpha: 0.5, tension: self.tension }),
+                InterpKind::Chordal => Box::new(CatmullRom { alpha: 1.0, tension: self.tension }),
+                InterpKind::Natural => Box::new(NaturalCubic),
+                InterpKind::NaturalX => Box::new(NaturalX { domain: None }),
+                InterpKind::BSpline => Box::new(BSpline),
+                InterpKind::Bezier => Box::new(Bezier),
+                InterpKind::Akima => Box::new(Akima),
+            }
+        }
+
+        /// `segments`, or `default` when it is not set.
+        pub fn segments_or(&self, default: u32) -> u32 {
+            self.segments.unwrap_or(default)
+        }
+
+        /// The same interpolation measuring `spacing` on a `width` x `height` canvas, where the
+        /// unit square fills the largest centered square (see `screen::to_pixel`).
+        pub fn on_canvas(&self, width: usize, height: usize) -> Interpolation {
+            Interpolation { side: Some(width.min(height) as f64), ..self.clone() }
+        }
+
+        /// `spacing` in path units, on a 1000 pixel canvas unless placed `on_canvas`.
+        pub fn step(&self) -> Option<f64> {
+            self.spacing.map(|s| s / self.side.unwrap_or(1000.0))
+        }
+
+        /// `samples` resampled to `spacing`, or unchanged when it is not set.
+        pub fn resample(&self, samples: Array2<f64>) -> Array2<f64> {
+            match self.step() {
+                Some(step) => resample(&samples, step),
+                None => samples,
+            }
+        }
+    }
+
+    // resampling stops short of `step` beyond this many rows, e.g. for a natural-x spline that
+    // shoots far off the canvas between two nodes of almost the same x
+    const MAX_ROWS: usize = 1 << 20;
+
+    /// Evenly spaced rows along the polyline of `[x, y, ..]` rows, at most `step` apart and
+    /// keeping both ends; every column is interpolated linearly between the rows around it.
+    pub fn resample(samples: &Array2<f64>, step: f64) -> Array2<f64> {
+        let rows = samples.nrows();
+        let mut lengths = Vec::with_capacity(rows);
+        let mut total = 0.0;
+        for r in 0..rows {
+            if r > 0 {
+                total += (samples[[r, 0]] - samples[[r - 1, 0]]).hypot(samples[[r, 1]] - samples[[r - 1, 1]]);
+            }
+            lengths.push(total);
+        }
+        if rows < 2 || total <= 0.0 || step.is_nan() || step <= 0.0 {
+            return samples.clone();
+        }
+
+        let n = (total / step).ceil().clamp(1.0, MAX_ROWS as f64) as usize;
+        let mut out = Array2::<f64>::zeros((n + 1, samples.ncols()));
+        let mut r = 0;
+        for k in 0..=n {
+            let s = total * k as f64 / n as f64;
+            while r + 2 < rows && lengths[r + 1] < s {
+                r += 1;
+            }
+            let span = lengths[r + 1] - lengths[r];
+            let t = if span > 0.0 { ((s - lengths[r]) / span).clamp(0.0, 1.0) } else { 0.0 };
+            for c in 0..samples.ncols() {
+                out[[k, c]] = samples[[r, c]] + (samples[[r + 1, c]] - samples[[r, c]]) * t;
+            }
+        }
+        out
+    }
+
+    type P = [f64; 2];
+
+    // control point i, wrapped around for closed curves and clamped to the ends for open ones
+    fn at(xy: &Array2<f64>, i: isize, closed: bool) -> P {
+        let n = xy.nrows() as isize;
+        let i = if closed { i.rem_euclid(n) } else { i.max(0).min(n - 1) };
+        [xy[[i as usize, 0]], xy[[i as usize, 1]]]
+    }
+
+    fn spans(xy: &Array2<f64>, closed: bool) -> usize {
+        if closed { xy.nrows() } else { xy.nrows() - 1 }
+    }
+
+    fn end_point(xy: &Array2<f64>, closed: bool) -> P {
+        at(xy, if closed { 0 } else { xy.nrows() as isize - 1 }, closed)
+    }
+
+    // cubic Hermite spans: `tangents(i)` gives the tangents at both ends of span i, scaled to a
+    // unit parameter; the arithmetic matches the cubic_spline crate so cardinal output is unchanged
+    fn hermite<F: Fn(usize) -> (P, P)>(xy: &Array2<f64>, segments: u32, closed: bool, tangents: F) -> Array2<f64> {
+        let spans = spans(xy, closed);
+        let segs = segments as usize;
+        let mut out = Array2::<f64>::zeros((spans * segs + 1, 2));
+        for i in 0..spans {
+            let (curr, next) = (at(xy, i as isize, closed), at(xy, i as isize + 1, closed));
+            let (t1, t2) = tangents(i);
+            for t in 0..segs {
+                let st = t as f64 / segments as f64;
+                let (st_pow2, st_pow3) = (st.powi(2), st.powi(3));
+                let (st_pow2x3, st_pow3x2) = (3.0 * st_pow2, 2.0 * st_pow3);
+                let c1 = st_pow3x2 - st_pow2x3 + 1.0;
+                let c2 = -st_pow3x2 + st_pow2x3;
+                let c3 = st_pow3 - 2.0 * st_pow2 + st;
+                let c4 = st_pow3 - st_pow2;
+                for c in 0..2 {
+                    out[[i * segs + t, c]] = c1 * curr[c] + c2 * next[c] + c3 * t1[c] + c4 * t2[c];
+                }
+            }
+        }
+        let end = end_point(xy, closed);
+        out[[spans * segs, 0]] = end[0];
+        out[[spans * segs, 1]] = end[1];
+        out
+    }
+
+    pub struct Cardinal {
+        pub tension: f64,
+    }
+
+    impl Interpolator for Cardinal {
+        fn interpolate(&self, xy: &Array2<f64>, segments: u32, closed: bool) -> Array2<f64> {
+            hermite(xy, segments, closed, |i| {
+                let i = i as isize;
+                let (prev, curr, next, next2) = (at(xy, i - 1, closed), at(xy, i, closed), at(xy, i + 1, closed), at(xy, i + 2, closed));
+                (
+                    [(next[0] - prev[0]) * self.tension, (next[1] - prev[1]) * self.tension],
+                    [(next2[0] - curr[0]) * self.tension, (next2[1] - curr[1]) * self.tension],
+                )
+            })
+        }
+    }
+
+    /// Non-uniform Catmull-Rom: knots `alpha` = 0.5 apart for centripetal, 1.0 for chordal.
+    pub struct CatmullRom {
+        pub alpha: f64,
+        pub tension: f64,
+    }
+
+    impl Interpolator for CatmullRom {
+        fn interpolate(&self, xy: &Array2<f64>, segments: u32, closed: bool) -> Array2<f64> {
+            let knot = |a: P, b: P| (a[0] - b[0]).hypot(a[1] - b[1]).powf(self.alpha);
+            hermite(xy, segments, closed, |i| {
+                let i = i as isize;
+                let (p0, p1, p2, p3) = (at(xy, i - 1, closed), at(xy, i, closed), at(xy, i + 1, closed), at(xy, i + 2, closed));
+                let mut d1 = knot(p1, p2);
+                if d1 < 1e-12 {
+                    d1 = 1.0;
+                }
+                // repeated end points get the span's own spacing
+                let d0 = Some(knot(p0, p1)).filter(|d| *d >= 1e-12).unwrap_or(d1);
+                let d2 = Some(knot(p2, p3)).filter(|d| *d >= 1e-12).unwrap_or(d1);
+                // tension 0.5 is the plain Catmull-Rom tangent
+                let s = 2.0 * self.tension * d1;
+                let mut m1 = [0.0; 2];
+                let mut m2 = [0.0; 2];
+                for c in 0..2 {
+                    m1[c] = s * ((p1[c] - p0[c]) / d0 - (p2[c] - p0[c]) / (d0 + d1) + (p2[c] - p1[c]) / d1);
+                    m2[c] = s * ((p2[c] - p1[c]) / d1 - (p3[c] - p1[c]) / (d1 + d2) + (p3[c] - p2[c]) / d2);
+                }
+                (m1, m2)
+            })
+        }
+    }
+
+    pub struct NaturalCubic;
+
+    // second derivatives of a natural (or for closed curves, periodic) cubic spline through `y`
+    // with unit spacing: M[i-1] + 4 M[i] + M[i+1] = 6 (y[i+1] - 2 y[i] + y[i-1])
+    fn second_derivatives(y: &[f64], closed: bool) -> Vec<f64> {
+        let n = y.len();
+        if closed {
+            let d: Vec<f64> = (0..n).map(|i| 6.0 * (y[(i + 1) % n] - 2.0 * y[i] + y[(i + n - 1) % n])).collect();
+            return solve_cyclic(&d);
+        }
+        let mut m = vec![0.0; n];
+        if n > 2 {
+            let d: Vec<f64> = (1..n - 1).map(|i| 6.0 * (y[i + 1] - 2.0 * y[i] + y[i - 1])).collect();
+            m[1..n - 1].copy_from_slice(&solve_tridiagonal(&vec![4.0; n - 2], &vec![1.0; n - 3], &d));
+        }
+        m
+    }
+
+    // Thomas algorithm for a symmetric matrix, `off[i]` joining rows i and i + 1
+    fn solve_tridiagonal(diag: &[f64], off: &[f64], d: &[f64]) -> Vec<f64> {
+        let n = d.len();
+        let e = |i: usize| off.get(i).copied().unwrap_or(0.0);
+        let mut c = vec![0.0; n];
+        let mut x = vec![0.0; n];
+        c[0] = e(0) / diag[0];
+        x[0] = d[0] / diag[0];
+        for i in 1..n {
+            let m = diag[i] - e(i - 1) * c[i - 1];
+            c[i] = e(i) / m;
+            x[i] = (d[i] - e(i - 1) * x[i - 1]) / m;
+        }
+        for i in (0..n - 1).rev() {
+            x[i] -= c[i] * x[i + 1];
+        }
+        x
+    }
+
+    // the same with ones in the corners too, by Sherman-Morrison
+    fn solve_cyclic(d: &[f64]) -> Vec<f64> {
+        let n = d.len();
+        let gamma = -4.0;
+        let mut diag = vec![4.0; n];
+        diag[0] -= gamma;
+        diag[n - 1] -= 1.0 / gamma;
+        let ones = vec![1.0; n - 1];
+        let x = solve_tridiagonal(&diag, &ones, d);
+        let mut u = vec![0.0; n];
+        u[0] = gamma;
+        u[n - 1] = 1.0;
+        let z = solve_tridiagonal(&diag, &ones, &u);
+        let k = (x[0] + x[n - 1] / gamma) / (1.0 + z[0] + z[n - 1] / gamma);
+        x.iter().zip(z.iter()).map(|(x, z)| x - k * z).collect()
+    }
+
+    impl Interpolator for NaturalCubic {
+        fn interpolate(&self, xy: &Array2<f64>, segments: u32, closed: bool) -> Array2<f64> {
+            let mx = second_derivatives(&xy.column(0).to_vec(), closed);
+            let my = second_derivatives(&xy.column(1).to_vec(), closed);
+            let n = xy.nrows();
+            hermite(xy, segments, closed, |i| {
+                let j = (i + 1) % n;
+                let (a, b) = (at(xy, i as isize, closed), at(xy, j as isize, closed));
+                let m = [(mx[i], mx[j]), (my[i], my[j])];
+                let mut t1 = [0.0; 2];
+                let mut t2 = [0.0; 2];
+                for c in 0..2 {
+                    let delta = b[c] - a[c];
+                    t1[c] = delta - m[c].0 / 3.0 - m[c].1 / 6.0;
+                    t2[c] = delta + m[c].0 / 6.0 + m[c].1 / 3.0;
+                }
+                (t1, t2)
+            })
+        }
+    }
+
+    /// Natural cubic y(x) through the points sorted by x, one y per x, sampled at evenly spaced x
+    /// over `domain` (the points' own range when `None`) and continued by the end pieces beyond
+    /// them. `closed` is ignored.
+    pub struct NaturalX {
+        pub domain: Option<(f64, f64)>,
+    }
+
+    impl Interpolator for NaturalX {
+        fn interpolate(&self, xy: &Array2<f64>, segments: u32, _closed: bool) -> Array2<f64> {
+            let mut nodes: Vec<P> = xy.rows().into_iter().map(|r| [r[0], r[1]]).collect();
+            nodes.sort_by(|a, b| a[0].total_cmp(&b[0]));
+            nodes.dedup_by(|a, b| a[0] - b[0] < 1e-12);
+            let n = nodes.len();
+            let h: Vec<f64> = nodes.windows(2).map(|w| w[1][0] - w[0][0]).collect();
+
+            // second derivatives, zero at both ends:
+            // h[i-1] M[i-1] + 2 (h[i-1] + h[i]) M[i] + h[i] M[i+1] = 6 (slope[i] - slope[i-1])
+            let mut m = vec![0.0; n];
+            if n > 2 {
+                let slope: Vec<f64> = (0..n - 1).map(|i| (nodes[i + 1][1] - nodes[i][1]) / h[i]).collect();
+                let diag: Vec<f64> = (1..n - 1).map(|i| 2.0 * (h[i - 1] + h[i])).collect();
+                let d: Vec<f64> = (1..n - 1).map(|i| 6.0 * (slope[i] - slope[i - 1])).collect();
+                m[1..n - 1].copy_from_slice(&solve_tridiagonal(&diag, &h[1..n - 2], &d));
+            }
+
+            let (from, to) = self.domain.unwrap_or((nodes[0][0], nodes[n - 1][0]));
+            let count = spans(xy, false).max(1) * segments as usize + 1;
+            let mut out = Array2::<f64>::zeros((count, 2));
+            for r in 0..count {
+                let x = from + (to - from) * r as f64 / (count - 1).max(1) as f64;
+                out[[r, 0]] = x;
+                out[[r, 1]] = if n < 2 {
+                    nodes[0][1]
+                } else {
+                    let i = nodes.partition_point(|p| p[0] <= x).clamp(1, n - 1) - 1;
+                    let (a, b) = (nodes[i], nodes[i + 1]);
+                    let (u, v) = (b[0] - x, x - a[0]);
+                    m[i] * u.powi(3) / (6.0 * h[i]) + m[i + 1] * v.powi(3) / (6.0 * h[i])
+                        + (a[1] / h[i] - m[i] * h[i] / 6.0) * u + (b[1] / h[i] - m[i + 1] * h[i] / 6.0) * v
+                };
+            }
+            out
+        }
+    }
+
+    pub struct BSpline;
+
+    impl Interpolator for BSpline {
+        fn interpolate(&self, xy: &Array2<f64>, segments: u32, closed: bool) -> Array2<f64> {
+            let n = xy.nrows() as isize;
+            // open curves triple their end points so they start and end on them
+            let (first, spans) = if closed { (-1, n) } else { (-2, n + 1) };
+            let segs = segments as usize;
+            let mut out = Array2::<f64>::zeros((spans as usize * segs + 1, 2));
+            for s in 0..spans {
+                let i = first + s;
+                let p = [at(xy, i, closed), at(xy, i + 1, closed), at(xy, i + 2, closed), at(xy, i + 3, closed)];
+                for t in 0..segs {
+                    let u = t as f64 / segments as f64;
+                    let (u2, u3) = (u * u, u * u * u);
+                    let b = [
+                        (1.0 - u).powi(3) / 6.0,
+                        (3.0 * u3 - 6.0 * u2 + 4.0) / 6.0,
+                        (-3.0 * u3 + 3.0 * u2 + 3.0 * u + 1.0) / 6.0,
+                        u3 / 6.0,
+                    ];
+                    for c in 0..2 {
+                        out[[s as usize * segs + t, c]] = b[0] * p[0][c] + b[1] * p[1][c] + b[2] * p[2][c] + b[3] * p[3][c];
+                    }
+                }
+            }
+            let last = spans as usize * segs;
+            let end = if closed { out.row(0).to_owned() } else { ndarray::arr1(&end_point(xy, closed)) };
+            out.row_mut(last).assign(&end);
+            out
+        }
+    }
+
+    pub struct Bezier;
+
+    // Bernstein weights of degree `d` at `t`, from the end nearer to `t` so that they do not underflow
+    fn bernstein(d: usize, t: f64) -> Vec<f64> {
+        if t > 0.5 {
+            let mut w = bernstein(d, 1.0 - t);
+            w.reverse();
+            return w;
+        }
+        let mut w = vec![0.0; d + 1];
+        w[0] = (1.0 - t).powi(d as i32);
+        for k in 0..d {
+            w[k + 1] = w[k] * (d - k) as f64 / (k + 1) as f64 * t / (1.0 - t);
+        }
+        w
+    }
+
+    impl Interpolator for Bezier {
+        fn interpolate(&self, xy: &Array2<f64>, segments: u32, closed: bool) -> Array2<f64> {
+            let spans = spans(xy, closed);
+            let controls: Vec<P> = (0..=spans as isize).map(|i| at(xy, i, closed)).collect();
+            let d = controls.len() - 1;
+            let count = spans * segments as usize;
+            let mut out = Array2::<f64>::zeros((count + 1, 2));
+            for r in 0..=count {
+                let w = bernstein(d, r as f64 / count as f64);
+                for (k, p) in controls.iter().enumerate() {
+                    out[[r, 0]] += w[k] * p[0];
+                    out[[r, 1]] += w[k] * p[1];
+                }
+            }
+            out
+        }
+    }
+
+    pub struct Akima;
+
+    // Akima tangents of a coordinate at every control point, unit spacing
+    fn akima_tangents(y: ArrayView1<f64>, closed: bool) -> Vec<f64> {
+        let n = y.len() as isize;
+        let spans = if closed { n } else { n - 1 };
+        let raw: Vec<f64> = (0..spans).map(|i| y[((i + 1) % n) as usize] - y[i as usize]).collect();
+        // slopes beyond the ends of an open curve continue linearly
+        let m = |i: isize| -> f64 {
+            if closed {
+                return raw[i.rem_euclid(spans) as usize];
+            }
+            let last = spans - 1;
+            if i < 0 {
+                let (m0, m1) = (raw[0], raw[1.min(last) as usize]);
+                m0 + (m0 - m1) * (-i) as f64
+            } else if i > last {
+                let (ml, mp) = (raw[last as usize], raw[(last - 1).max(0) as usize]);
+                ml + (ml - mp) * (i - last) as f64
+            } else {
+                raw[i as usize]
+            }
+        };
+        (0..n).map(|i| {
+            let w1 = (m(i + 1) - m(i)).abs();
+            let w2 = (m(i - 1) - m(i - 2)).abs();
+            if w1 + w2 < 1e-12 {
+                (m(i - 1) + m(i)) / 2.0
+            } else {
+                (w1 * m(i - 1) + w2 * m(i)) / (w1 + w2)
+            }
+        }).collect()
+    }
+
+    impl Interpolator for Akima {
+        fn interpolate(&self, xy: &Array2<f64>, segments: u32, closed: bool) -> Array2<f64> {
+            let tx = akima_tangents(xy.column(0), closed);
+            let ty = akima_tangents(xy.column(1), closed);
+            let n = xy.nrows();
+            hermite(xy, segments, closed, |i| {
+                let j = (i + 1) % n;
+                ([tx[i], ty[i]], [tx[j], ty[j]])
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ndarray::arr2;
+
+        fn close(a: f64, b: f64) -> bool {
+            (a - b).abs() < 1e-9
+        }
+
+        #[test]
+        fn cardinal_matches_cubic_spline() {
+            // cubic_spline 1.0.0, tension 0.5 and 4 segments, which the circle modes used to call
+            let expected = arr2(&[
+                [0.0, 0.0], [0.15625, 0.4296875], [0.375, 1.0625], [0.65625, 1.6640625],
+                [1.0, 2.0], [1.453125, 1.890625], [2.0, 1.5], [2.546875, 1.109375],
+                [3.0, 1.0], [3.34375, 1.3359375], [3.625, 1.9375], [3.84375, 2.5703125],
+                [4.0, 3.0],
+            ]);
+            let xy = arr2(&[[0.0, 0.0], [1.0, 2.0], [3.0, 1.0], [4.0, 3.0]]);
+            assert_eq!(Cardinal { tension: 0.5 }.interpolate(&xy, 4, false), expected);
+        }
+
+        #[test]
+        fn closed_natural_cubic_is_smooth_at_the_seam() {
+            let xy = arr2(&[[1.0, 0.0], [0.0, 1.0], [-1.0, 0.0], [0.0, -0.5]]);
+            let out = NaturalCubic.interpolate(&xy, 1000, true);
+            let last = out.nrows() - 1;
+            assert_eq!(out.row(0), out.row(last));
+            for c in 0..2 {
+                let leaving = out[[1, c]] - out[[0, c]];
+                let arriving = out[[last, c]] - out[[last - 1, c]];
+                assert!((leaving - arriving).abs() < 1e-5, "column {}: {} vs {}", c, leaving, arriving);
+            }
+        }
+
+        #[test]
+        fn natural_x_matches_a_hand_computed_spline() {
+            // through (0, 0), (1, 1), (3, 0): M1 = -1.5, so y = 1.25 x - 0.25 x^3 left of 1
+            // and y = u - u^3 / 8 with u = 3 - x right of it
+            let xy = arr2(&[[3.0, 0.0], [0.0, 0.0], [1.0, 1.0]]);
+            let out = NaturalX { domain: Some((-1.0, 3.0)) }.interpolate(&xy, 2, false);
+            let expected = [[-1.0, -1.0], [0.0, 0.0], [1.0, 1.0], [2.0, 0.875], [3.0, 0.0]];
+            assert_eq!(out.nrows(), expected.len());
+            for (row, e) in out.rows().into_iter().zip(expected.iter()) {
+                assert!(close(row[0], e[0]) && close(row[1], e[1]), "{:?} vs {:?}", row, e);
+            }
+        }
+
+        #[test]
+        fn solves_tridiagonal_systems() {
+            // [4 1 0; 1 5 2; 0 2 6] x = d for x = [1, -1, 2]
+            let x = solve_tridiagonal(&[4.0, 5.0, 6.0], &[1.0, 2.0], &[3.0, 0.0, 10.0]);
+            for (x, e) in x.iter().zip([1.0, -1.0, 2.0]) {
+                assert!(close(*x, e), "{:?}", x);
+            }
+            // 4 on the diagonal, ones next to it and in the corners, for x = [1, 2, 3, 4]
+            let x = solve_cyclic(&[10.0, 12.0, 18.0, 20.0]);
+            for (x, e) in x.iter().zip([1.0, 2.0, 3.0, 4.0]) {
+                assert!(close(*x, e), "{:?}", x);
+            }
+        }
+
+        #[test]
+        fn resample_stops_at_max_rows() {
+            let out = resample(&arr2(&[[0.0, 0.0], [1.0, 0.0]]), 1e-9);
+            assert_eq!(out.nrows(), MAX_ROWS + 1);
+            assert_eq!(out[[MAX_ROWS, 0]], 1.0);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+pub mod job {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::thread::{self, JoinHandle};
+    use ndarray::Array2;
+
+    use crate::scene::scene::{self, Scene};
+    use crate::screen::screen::{Argb, Gradient, Screen};
+    use crate::sink::sink::SampleSink;
+    use crate::spl::spl;
+
+    /// Batches sent so far out of the batches a scene will send, shared with the drawing threads.
+    pub struct Progress {
+        done: AtomicUsize,
+        total: usize,
+        cancelled: AtomicBool,
+        paused: Mutex<bool>,
+        resumed: Condvar,
+    }
+
+    impl Progress {
+        pub fn new(total: usize) -> Arc<Progress> {
+            Arc::new(Progress {
+                done: AtomicUsize::new(0),
+                total,
+                cancelled: AtomicBool::new(false),
+                paused: Mutex::new(false),
+                resumed: Condvar::new(),
+            })
+        }
+
+        pub fn done(&self) -> usize {
+            self.done.load(Ordering::Relaxed)
+        }
+
+        pub fn total(&self) -> usize {
+            self.total
+        }
+
+        pub fn fraction(&self) -> f64 {
+            if self.total == 0 { 1.0 } else { (self.done() as f64 / self.total as f64).min(1.0) }
+        }
+
+        /// Generators notice at their next batch and return.
+        pub fn cancel(&self) {
+            self.cancelled.store(true, Ordering::Relaxed);
+            let _p = self.paused.lock().unwrap();
+            self.resumed.notify_all();
+        }
+
+        pub fn is_cancelled(&self) -> bool {
+            self.cancelled.load(Ordering::Relaxed)
+        }
+
+        /// While paused, generators block before sending their next batch.
+        pub fn set_paused(&self, paused: bool) {
+            *self.paused.lock().unwrap() = paused;
+            self.resumed.notify_all();
+        }
+
+        pub fn is_paused(&self) -> bool {
+            *self.paused.lock().unwrap()
+        }
+
+        fn wait_while_paused(&self) {
+            let mut paused = self.paused.lock().unwrap();
+            while *paused && !self.is_cancelled() {
+                paused = self.resumed.wait(paused).unwrap();
+            }
+        }
+    }
+
+    /// Counts every batch passed on to `inner`, holds it back while paused and closes once the job is cancelled.
+    pub struct Tracked<S> {
+        pub inner: S,
+        progress: Arc<Progress>,
+    }
+
+    impl<S> Tracked<S> {
+        pub fn new(inner: S, progress: Arc<Progress>) -> Self {
+            Tracked { inner, progress }
+        }
+    }
+
+    impl<S: SampleSink> SampleSink for Tracked<S> {
+        fn send(&mut self, samples: Array2<f64>) {
+            self.progress.wait_while_paused();
+            if self.progress.is_cancelled() {
+                return;
+            }
+            self.inner.send(samples);
+            self.progress.done.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn is_closed(&self) -> bool {
+            self.progress.is_cancelled() || self.inner.is_closed()
+        }
+
+        fn set_style(&mut self, color: Argb, gradient: Option<&Gradient>) {
+            self.inner.set_style(color, gradient);
+        }
+    }
+
+    /// A scene being drawn on a background thread.
+    pub struct RenderJob {
+        screen: Arc<Mutex<Screen>>,
+        progress: Arc<Progress>,
+        handle: Option<JoinHandle<()>>,
+    }
+
+    /// Starts drawing `scene` with its seed (0 if it has none).
+    pub fn start(scene: &Scene) -> RenderJob {
+        let screen = scene::canvas(scene);
+        let progress = Progress::new(scene.layers.iter().map(|l| l.generator.batches()).sum());
+        let mut rng = spl::seeded(scene.seed.unwrap_or(0));
+
+        let sc = screen.clone();
+        let p = progress.clone();
+        let scene = scene.clone();
+        let handle = thread::spawn(move || {
+            scene::draw(sc, &scene, &mut rng, &p);
+        });
+        RenderJob { screen, progress, handle: Some(handle) }
+    }
+
+    impl RenderJob {
+        /// The canvas being drawn, e.g. to show it while the job runs.
+        pub fn screen(&self) -> Arc<Mutex<Screen>> {
+            self.screen.clone()
+        }
+
+        pub fn progress(&self) -> Arc<Progress> {
+            self.progress.clone()
+        }
+
+        pub fn is_finished(&self) -> bool {
+            self.handle.as_ref().is_none_or(|h| h.is_finished())
+        }
+
+        pub fn cancel(&self) {
+            self.progress.cancel();
+        }
+
+        /// Waits for every drawing thread and returns the final canvas; a generator panic is passed on.
+        pub fn finish(mut self) -> Arc<Mutex<Screen>> {
+            if let Some(h) = self.handle.take() {
+                if let Err(e) = h.join() {
+                    std::panic::resume_unwind(e);
+                }
+            }
+            self.screen.lock().unwrap().resolve();
+            self.screen.clone()
+        }
+    }
+
+    impl Drop for RenderJob {
+        // an abandoned job stops drawing instead of running on in the background
+        fn drop(&mut self) {
+            if let Some(h) = self.handle.take() {
+                self.progress.cancel();
+                let _ = h.join();
+            }
+        }
+    }
+}
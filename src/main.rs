@@ -1,179 +1,344 @@
-use core::time;
-use std::{env, thread};
-use std::sync::{Arc, Mutex};
+use std::{process, thread};
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::time::Duration;
+pub mod cli;
+pub mod config;
+pub mod curve;
+pub mod fourier;
+pub mod gallery;
+pub mod interp;
+pub mod job;
+pub mod noise;
+pub mod plot;
+pub mod scene;
 pub mod screen;
+pub mod sink;
 pub mod spl;
+pub mod svg;
+pub mod viewer;
 
+use clap::Parser;
 use rand::Rng;
+use rand::rngs::StdRng;
 
-use crate::screen::screen::{set_color, set_bg, render, renderWithTimeout};
+use crate::cli::cli::{Cli, Mode, CircleSplineArgs, OutlineArgs, ScriptArgs, HieroglyphArgs};
+use crate::config::config::{self as cfg, ConfigError, Validate};
+use crate::curve::curve::CurveKind;
+use crate::gallery::gallery::GalleryOpts;
+use crate::plot::plot::{Page, PlotFormat, PlotOpts, Strokes};
+use crate::scene::scene::{Scene, Layer, Generator};
+use crate::spl::spl::ComplexEquationParams;
+use crate::screen::screen::{BlendMode, Splat, save_png};
+use crate::viewer::viewer::{Exit, ViewerOpts};
 fn main() {
-    //paint_circle_spline();
-    //paint_complex_spline();
-    paint_random();
-    //paint_rand_splines();
-    //paint_complex_circle();
-    //paint_script();
-    //paint_hieroglyphs();
-}
-
-fn paint_rand_splines() {
-    let s = screen::screen::new(1000,1000);
-    let m = Mutex::new(s);
-    let sm = Arc::new(m);
-    set_color(sm.clone(), [0.3, 1.0, 1.0, 1.0]);
-    set_bg(sm.clone(), [0.02, 0.05, 0.05, 0.05]);
-    spl::spl::fill_rand_splines(sm.clone());
-    render(sm.clone());
-}
-
-fn paint_circle_spline() {
-    let s = screen::screen::new(1000,1000);
-    let m = Mutex::new(s);
-    let sm = Arc::new(m);
-    set_color(sm.clone(), [0.02, 1.0, 1.0, 1.0]);
-    set_bg(sm.clone(), [0.02, 0.1, 0.35, 0.7]);
-
-    let mut ncircles = 3;
-    match env::var("N") {
-        Ok(v) => {
-            ncircles = v.parse::< usize >().unwrap();
+    let cli = Cli::parse();
+
+    let mut scene = match &cli.mode {
+        Mode::Random { count, opts, gallery, columns, thumb } => {
+            match gallery {
+                Some(dir) => paint_gallery(&cli, *count, opts, GalleryOpts {
+                    dir: dir.clone(),
+                    columns: *columns,
+                    thumb: *thumb,
+                    threads: cli.threads.unwrap_or_else(scene::scene::default_threads),
+                }),
+                None => paint_random(&cli, *count, opts),
+            }
+            return;
         },
-        Err(_) => {}
+        Mode::Scene { file } => scene::scene::load(file).unwrap_or_else(|e| {
+            eprintln!("{}: {}", file, e);
+            process::exit(1);
+        }),
+        Mode::Circle { n, opts } => circle_scene(*n, opts).unwrap_or_else(|e| fail(e)),
+        Mode::Complex { opts, params, outline } => complex_scene(opts, equation(params, outline)),
+        Mode::Curve { shape, opts, size } => curve_scene(*shape, opts, *size),
+        Mode::RandSplines => rand_splines_scene(),
+        Mode::ComplexCircle { params, outline } => complex_circle_scene(equation(params, outline)),
+        Mode::Script(opts) => script_scene(opts),
+        Mode::Hieroglyphs(opts) => hieroglyphs_scene(opts),
+    };
+
+    apply_overrides(&cli, &mut scene);
+    scene.seed = Some(report_seed(cli.seed.or(scene.seed)));
+
+    if let Some(path) = &cli.save_scene {
+        scene::scene::save(&scene, path).unwrap();
+        println!("saved {}", path);
+    }
+    paint_scene(&cli, scene, None);
+}
+
+// command line flags override whatever the scene says, then the result is validated
+fn apply_overrides(cli: &Cli, scene: &mut Scene) {
+    if let Some(w) = cli.width { scene.width = w; }
+    if let Some(h) = cli.height { scene.height = h; }
+    if let Some(bg) = cli.bg { scene.bg = bg; }
+    if let Some(splat) = cli.splat { scene.splat = splat; }
+    if let Some(blend) = cli.blend { scene.blend = blend; }
+    if cli.threads.is_some() { scene.threads = cli.threads; }
+    if cli.tone.is_some() { scene.tone = cli.tone; }
+    if let Some(color) = cli.color {
+        for layer in scene.layers.iter_mut() {
+            layer.color = color;
+        }
+    }
+    for layer in scene.layers.iter_mut() {
+        if cli.interp.is_some() || cli.tension.is_some() || cli.segments.is_some() || cli.spacing.is_some() {
+            let interp = layer.interpolation.get_or_insert_with(Default::default);
+            if cli.interp.is_some() { interp.kind = cli.interp; }
+            if let Some(v) = cli.tension { interp.tension = v; }
+            if cli.segments.is_some() { interp.segments = cli.segments; }
+            if cli.spacing.is_some() { interp.spacing = cli.spacing; }
+        }
+        if let Some(g) = cli.gradient() {
+            layer.gradient = Some(g);
+        } else if let (Some(g), Some(by)) = (layer.gradient.as_mut(), cli.gradient_by) {
+            g.by = by;
+        }
+    }
+    if let Err(e) = scene.validate("") {
+        fail(e);
+    }
+}
+
+fn fail(e: ConfigError) -> ! {
+    eprintln!("error: {}", e);
+    if let ConfigError::Env { .. } = e {
+        eprintln!("\n{}", cfg::ENV_HELP);
+    }
+    process::exit(2);
+}
+
+// --seed / SEED reproduces a previous run, otherwise a fresh seed is drawn; either way it is printed
+fn report_seed(seed: Option<u64>) -> u64 {
+    let seed = seed.unwrap_or_else(rand::random::<u64>);
+    println!("seed: {}", seed);
+    seed
+}
+
+// --output / OUTPUT switches to headless mode: the result is saved instead of shown in a window,
+// as SVG paths for *.svg, plotter strokes for G-code/HPGL and as PNG otherwise; `index` numbers
+// the output of one scene out of several
+fn paint_scene(cli: &Cli, scene: Scene, index: Option<usize>) -> Exit {
+    let path = match (&cli.output, index) {
+        (None, _) => return viewer::viewer::view(scene, &ViewerOpts { timeout: cli.timeout }),
+        (Some(path), Some(i)) => numbered_path(path, i),
+        (Some(path), None) => path.clone(),
+    };
+    let saved = if Path::new(&path).extension().is_some_and(|e| e.eq_ignore_ascii_case("svg")) {
+        save_svg(cli, &scene, &path)
+    } else if let Some(format) = PlotFormat::from_path(&path) {
+        save_plot(cli, &scene, &path, format)
+    } else {
+        save_raster(&scene, &path)
+    };
+    if let Err(e) = saved {
+        eprintln!("{}: {}", path, e);
+        process::exit(1);
+    }
+    println!("saved {}", path);
+    Exit::Closed
+}
+
+fn save_raster(scene: &Scene, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let job = job::job::start(scene);
+    let progress = job.progress();
+    let tty = std::io::stderr().is_terminal();
+    while !job.is_finished() {
+        if tty {
+            eprint!("\rdrawing {:3.0}%", progress.fraction() * 100.0);
+            std::io::stderr().flush().unwrap();
+        }
+        thread::sleep(Duration::from_millis(200));
     }
+    if tty {
+        eprintln!("\rdrawing 100%");
+    }
+    save_png(job.finish(), path)?;
+    Ok(())
+}
 
+// vector outputs draw the scene straight into their sink, on this thread
+fn save_svg(cli: &Cli, scene: &Scene, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut out = svg::svg::Svg::create(path, scene, cli.stroke_width)?;
+    scene::scene::draw_into(&mut out, scene, &mut spl::spl::seeded(scene.seed.unwrap_or(0)));
+    out.finish()?;
+    Ok(())
+}
+
+fn save_plot(cli: &Cli, scene: &Scene, path: &str, format: PlotFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let mut strokes = Strokes::default();
+    scene::scene::draw_into(&mut strokes, scene, &mut spl::spl::seeded(scene.seed.unwrap_or(0)));
+    let opts = PlotOpts {
+        format,
+        page: Page { margin: cli.margin, ..cli.page },
+        pen_up: cli.pen_up.clone(),
+        pen_down: cli.pen_down.clone(),
+        feed: cli.feed,
+    };
+    let count = strokes.paths.len();
+    let (before, after) = plot::plot::save(strokes, path, &opts)?;
+    println!("{} strokes, pen-up travel {:.0} mm (unordered {:.0} mm)", count, after, before);
+    Ok(())
+}
+
+// art.png -> art-007.png
+fn numbered_path(path: &str, i: usize) -> String {
+    let p = Path::new(path);
+    let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or("out");
+    let ext = p.extension().and_then(|s| s.to_str()).unwrap_or("png");
+    p.with_file_name(format!("{}-{:03}.{}", stem, i, ext)).to_string_lossy().into_owned()
+}
+
+fn single_layer(bg: [f64; 4], color: [f64; 4], generator: Generator) -> Scene {
+    Scene {
+        width: 1000,
+        height: 1000,
+        bg,
+        splat: Splat::default(),
+        blend: BlendMode::default(),
+        tone: None,
+        threads: None,
+        seed: None,
+        layers: vec![Layer { color, gradient: None, seed: None, interpolation: None, generator }],
+    }
+}
+
+fn rand_splines_scene() -> Scene {
+    single_layer([1.0, 0.05, 0.05, 0.05], [0.3, 1.0, 1.0, 1.0], Generator::RandSplines)
+}
+
+fn circle_scene(ncircles: usize, args: &CircleSplineArgs) -> Result<Scene, ConfigError> {
     let mut opts = spl::spl::CircleSplineOpts{
-        inum: 200,
-        stp: 0.000002,
-        pnum_from: 100,
         pnum_to: 150,
-        iterations: 80,
+        ..Default::default()
     };
 
-    spl::spl::from_env(& mut opts);
+    cfg::from_env(& mut opts)?;
+    args.apply(& mut opts);
+
+    Ok(single_layer([1.0, 0.1, 0.35, 0.7], [0.02, 1.0, 1.0, 1.0], Generator::Circle { count: ncircles, opts }))
+}
+
+// the terms fitted to --outline, or those given with --param
+fn equation(params: &[[f64; 3]], outline: &OutlineArgs) -> ComplexEquationParams {
+    match &outline.outline {
+        Some(path) => {
+            let points = fourier::fourier::load(path).unwrap_or_else(|e| {
+                eprintln!("{}: {}", path, e);
+                process::exit(1);
+            });
+            let cc = fourier::fourier::fit(&points, outline.terms as usize);
+            println!("fitted {} terms to {} outline points", cc.params.len(), points.len());
+            cc
+        },
+        None => ComplexEquationParams::new(params.to_vec()),
+    }
+}
+
+fn complex_circle_scene(cc: ComplexEquationParams) -> Scene {
+    let cc = if cc.params.is_empty() { ComplexEquationParams::new(vec![[1.0, 1.0, 1.0]]) } else { cc };
+    single_layer([1.0, 0.0, 0.0, 0.0], [0.2, 0.6, 1.0, 0.5], Generator::ComplexCircle { params: cc.params, phases: cc.phases })
+}
+
+fn complex_scene(args: &CircleSplineArgs, cc: ComplexEquationParams) -> Scene {
+    let mut opts = spl::spl::CircleSplineOpts::default();
+    args.apply(& mut opts);
+
+    let cc = if cc.params.is_empty() {
+        ComplexEquationParams::new(vec![
+            [3.1, 1.0, 0.01],
+            [3.0, -3.0, 0.01],
+            [0.5, 0.5, 0.01]])
+    } else {
+        cc
+    };
+    single_layer([1.0, 0.0, 0.0, 0.0], [0.2, 0.6, 1.0, 0.5], Generator::Complex { opts, params: cc.params, phases: cc.phases })
+}
 
-    let _: Vec<_> = (0..ncircles).map(|_| { 
-        let sc = sm.clone();
-        let o = opts.clone();
-        thread::spawn(move || {  
-            spl::spl::fill_circle_splines(sc.clone(), o);
-        });
+fn curve_scene(curve: CurveKind, args: &CircleSplineArgs, size: f64) -> Scene {
+    let mut opts = spl::spl::CircleSplineOpts::default();
+    args.apply(& mut opts);
+
+    single_layer([1.0, 0.0, 0.0, 0.0], [0.2, 0.6, 1.0, 0.5], Generator::Curve { opts, curve, size })
+}
+
+fn random_scene(args: &CircleSplineArgs, rng: &mut StdRng) -> Scene {
+    let mut opts = spl::spl::CircleSplineOpts{
+        stp: 0.000001,
+        pnum_from: 150,
+        pnum_to: 181,
+        iterations: 50,
+        ..Default::default()
+    };
+    args.apply(& mut opts);
+
+    let params = vec![
+        [rng.gen_range(-5.0..5.0), rng.gen_range(-5.0..5.0), rng.gen_range(-0.1..0.1)],
+        [rng.gen_range(-5.0..5.0), rng.gen_range(-5.0..5.0), rng.gen_range(-0.1..0.1)],
+        [rng.gen_range(-5.0..5.0), rng.gen_range(-5.0..5.0), rng.gen_range(-0.1..0.1)],
+    ];
+    single_layer([1.0, 0.0, 0.0, 0.0], [0.2, 0.6, 1.0, 0.5], Generator::Complex { opts, params, phases: Vec::new() })
+}
+
+// one scene per candidate, each with its own seed; --save-scene and --output are numbered
+fn paint_random(cli: &Cli, count: usize, args: &CircleSplineArgs) {
+    let mut rng = spl::spl::seeded(report_seed(cli.seed));
+    for i in 0..count {
+        let mut scene = random_scene(args, &mut rng);
+        apply_overrides(cli, &mut scene);
+        scene.seed = Some(rng.gen());
+
+        if let Some(path) = &cli.save_scene {
+            let path = numbered_path(path, i);
+            scene::scene::save(&scene, &path).unwrap();
+            println!("saved {}", path);
+        }
+        if paint_scene(cli, scene, Some(i)) == Exit::Quit {
+            break;
+        }
+    }
+}
+
+// like paint_random, but every candidate is saved with its scene and a contact sheet is made
+fn paint_gallery(cli: &Cli, count: usize, args: &CircleSplineArgs, opts: GalleryOpts) {
+    let mut rng = spl::spl::seeded(report_seed(cli.seed));
+    let scenes: Vec<Scene> = (0..count).map(|_| {
+        let mut scene = random_scene(args, &mut rng);
+        apply_overrides(cli, &mut scene);
+        scene.seed = Some(rng.gen());
+        scene
     }).collect();
-    render(sm.clone());
-}
-
-fn paint_complex_circle() {
-    let s = screen::screen::new(1000,1000);
-    let m = Mutex::new(s);
-    let sm = Arc::new(m);
-    set_color(sm.clone(), [0.2, 0.6, 1.0, 0.5]);
-    set_bg(sm.clone(), [1.0, 0.0, 0.0, 0.0]);
-
-    spl::spl::fill_complex_circle(sm.clone(), vec![[1.0, 1.0, 1.0]]);
-    render(sm.clone());
-}
-
-fn paint_complex_spline() {
-    let s = screen::screen::new(1000,1000);
-    let m = Mutex::new(s);
-    let sm = Arc::new(m);
-    set_color(sm.clone(), [0.2, 0.6, 1.0, 0.5]);
-    set_bg(sm.clone(), [1.0, 0.0, 0.0, 0.0]);
-    
-    // let params = vec![
-    //     [1.0, 10.0, 0.01], 
-    //     [3.0, -3.0, 0.01], 
-    //     [0.5, -0.5, 0.001]];
-    // spl::spl::fill_complex_spline(sm.clone(), spl::spl::CircleSplineOpts{
-    //     inum: 200,
-    //     stp: 0.000002,
-    //     pnum_from: 100,
-    //     pnum_to: 141,
-    //     iterations: 40,
-    // }, params);
-
-    spl::spl::fill_complex_spline(sm.clone(), spl::spl::CircleSplineOpts{
-        inum: 200,
-        stp: 0.000002,
-        pnum_from: 100,
-        pnum_to: 151,
-        iterations: 80,
-    }, vec![
-        [3.1, 1.0, 0.01], 
-        [3.0, -3.0, 0.01], 
-        [0.5, 0.5, 0.01]]);
-
-    // spl::spl::fill_complex_spline(sm.clone(), spl::spl::CircleSplineOpts{
-    //     inum: 200,
-    //     stp: 0.000001,
-    //     pnum_from: 150,
-    //     pnum_to: 181,
-    //     iterations: 50,
-    // }, vec![
-    //         [2.1, 10.0, 0.01],
-    //         [3.0, -1.0, 0.01],
-    //         [0.5, 0.5, 0.01],
-    //     ]);
-
-    
-
-    render(sm.clone());
-}
-
-fn paint_random() {
-    let mut rng = rand::thread_rng();
-    for _ in 0..100 {
-        let s = screen::screen::new(1000,1000);
-        let m = Mutex::new(s);
-        let sm = Arc::new(m);
-        set_color(sm.clone(), [0.2, 0.6, 1.0, 0.5]);
-        set_bg(sm.clone(), [1.0, 0.0, 0.0, 0.0]);
-
-        spl::spl::fill_complex_spline(sm.clone(), spl::spl::CircleSplineOpts{
-            inum: 200,
-            stp: 0.000001,
-            pnum_from: 150,
-            pnum_to: 181,
-            iterations: 50,
-        }, vec![
-                [rng.gen_range(-5.0..5.0), rng.gen_range(-5.0..5.0), rng.gen_range(-0.1..0.1)],
-                [rng.gen_range(-5.0..5.0), rng.gen_range(-5.0..5.0), rng.gen_range(-0.1..0.1)],
-                [rng.gen_range(-5.0..5.0), rng.gen_range(-5.0..5.0), rng.gen_range(-0.1..0.1)],
-            ]);
-        renderWithTimeout(sm.clone());
+    if let Err(e) = gallery::gallery::render(&scenes, &opts) {
+        eprintln!("{}: {}", opts.dir, e);
+        process::exit(1);
     }
 }
 
-fn paint_script() {
-    let s = screen::screen::new(1000,1000);
-    let m = Mutex::new(s);
-    let sm = Arc::new(m);
-    set_color(sm.clone(), [0.1, 1.0, 1.0, 1.0]);
-    set_bg(sm.clone(), [0.1, 0.1, 0.15, 0.1]);
-    spl::spl::fill_spline_script(sm.clone(), spl::spl::ScriptOpts{
+fn script_scene(args: &ScriptArgs) -> Scene {
+    let mut opts = spl::spl::ScriptOpts{
         row_count: 12,
         col_count: 20,
         padding: 0.04,
         char_width: 0.02,
         height: 0.03,
         points: 6,
-    });
-    render(sm.clone());
+    };
+    args.apply(& mut opts);
+    single_layer([1.0, 0.1, 0.15, 0.1], [0.1, 1.0, 1.0, 1.0], Generator::Script(opts))
 }
 
-fn paint_hieroglyphs() {
-    let s = screen::screen::new(1000,1000);
-    let m = Mutex::new(s);
-    let sm = Arc::new(m);
-    set_color(sm.clone(), [0.1, 1.0, 1.0, 1.0]);
-    set_bg(sm.clone(), [0.1, 0.15, 0.35, 0.4]);
-    spl::spl::fill_spline_hieroglyph(sm.clone(), spl::spl::HieroglyphOpts {
+fn hieroglyphs_scene(args: &HieroglyphArgs) -> Scene {
+    let mut opts = spl::spl::HieroglyphOpts {
         row_count: 16,
         col_count: 16,
         padding:  0.02,
         width: 0.04,
         height: 0.03,
         points: 10,
-    });
-    render(sm.clone());
-}
\ No newline at end of file
+    };
+    args.apply(& mut opts);
+    single_layer([1.0, 0.15, 0.35, 0.4], [0.1, 1.0, 1.0, 1.0], Generator::Hieroglyphs(opts))
+}
@@ -0,0 +1,213 @@
+pub mod noise {
+    use std::f64::consts::PI;
+    use std::ops::{Add, Mul};
+    use ndarray::{Array, Array1, Array2};
+    use ndarray_rand::RandomExt;
+    use ndarray_rand::rand_distr::{StandardNormal, Uniform};
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use serde::{Deserialize, Serialize};
+
+    const TWOPI: f64 = 2.0 * PI;
+
+    // how far the smooth fields move along their time axis per evolution step
+    const TIME_STEP: f64 = 0.05;
+
+    /// How the control points of a spline move at each evolution step.
+    pub trait NoiseModel: Send {
+        /// Displacement of every control point of `path` (pnum x 2, centered on 0) at step `i`.
+        fn step(&mut self, path: &Array2<f64>, i: usize, rng: &mut StdRng) -> Array2<f64>;
+
+        /// Current noise amplitude of every control point, for the noise sample column.
+        fn amplitude(&self) -> &Array1<f64>;
+    }
+
+    /// Picks a `NoiseModel` per layer: `"random-walk"`, `"gaussian"`, `{ perlin = <frequency> }`,
+    /// `{ curl = <frequency> }` or `{ mean-reverting = <theta> }`.
+    #[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum NoiseKind {
+        /// Growing random steps in random directions, the original sand-spline jitter
+        #[default]
+        RandomWalk,
+        /// Like the random walk, with normally distributed offsets per axis
+        Gaussian,
+        /// Points follow a Perlin noise flow field with this many features across the drawing
+        Perlin(f64),
+        /// Points follow the curl of a Perlin field, swirling without bunching up
+        Curl(f64),
+        /// Random walk pulled back towards the initial shape by this fraction per step
+        MeanReverting(f64),
+    }
+
+    impl NoiseKind {
+        /// `scale` is the per point step size, see `spl::noise_scale`.
+        pub fn model(&self, path: &Array2<f64>, scale: Array1<f64>, rng: &mut StdRng) -> Box<dyn NoiseModel> {
+            let noise = Array1::<f64>::zeros(scale.len());
+            match *self {
+                NoiseKind::RandomWalk => Box::new(RandomWalk { scale, noise }),
+                NoiseKind::Gaussian => Box::new(Gaussian { scale, noise }),
+                NoiseKind::Perlin(frequency) => Box::new(Flow { scale, noise, frequency, curl: false, field: Perlin::new(rng) }),
+                NoiseKind::Curl(frequency) => Box::new(Flow { scale, noise, frequency, curl: true, field: Perlin::new(rng) }),
+                NoiseKind::MeanReverting(theta) => Box::new(MeanReverting { walk: RandomWalk { scale, noise }, origin: path.clone(), theta }),
+            }
+        }
+    }
+
+    fn polar(noise: &Array1<f64>, a: &Array1<f64>) -> Array2<f64> {
+        let x = a.map(|n| n.cos()).mul(noise);
+        let y = a.map(|n| n.sin()).mul(noise);
+        ndarray::stack(ndarray::Axis(1), &[x.view(), y.view()]).unwrap()
+    }
+
+    pub struct RandomWalk {
+        scale: Array1<f64>,
+        noise: Array1<f64>,
+    }
+
+    impl NoiseModel for RandomWalk {
+        fn step(&mut self, _path: &Array2<f64>, _i: usize, rng: &mut StdRng) -> Array2<f64> {
+            let pnum = self.scale.len();
+            let rand = Array::random_using(pnum, Uniform::new(-2.0, 0.0), rng);
+            let r = rand.map(|n| 1.0-n);
+            self.noise = self.noise.clone().add(r.mul(&self.scale));
+            let a = Array::random_using(pnum, Uniform::new(0.0, TWOPI), rng);
+            polar(&self.noise, &a)
+        }
+
+        fn amplitude(&self) -> &Array1<f64> {
+            &self.noise
+        }
+    }
+
+    pub struct Gaussian {
+        scale: Array1<f64>,
+        noise: Array1<f64>,
+    }
+
+    impl NoiseModel for Gaussian {
+        fn step(&mut self, _path: &Array2<f64>, _i: usize, rng: &mut StdRng) -> Array2<f64> {
+            // the random walk's mean growth, without its randomness
+            self.noise = self.noise.clone().add(self.scale.clone().mul(2.0));
+            let n = Array::random_using((self.noise.len(), 2), StandardNormal, rng);
+            let mut d: Array2<f64> = n;
+            for (mut row, s) in d.rows_mut().into_iter().zip(self.noise.iter()) {
+                row *= *s;
+            }
+            d
+        }
+
+        fn amplitude(&self) -> &Array1<f64> {
+            &self.noise
+        }
+    }
+
+    pub struct Flow {
+        scale: Array1<f64>,
+        noise: Array1<f64>,
+        frequency: f64,
+        curl: bool,
+        field: Perlin,
+    }
+
+    impl Flow {
+        fn direction(&self, x: f64, y: f64, t: f64) -> (f64, f64) {
+            let f = self.frequency;
+            if self.curl {
+                // (dP/dy, -dP/dx) of the potential, scaled to about unit length
+                let e = 1e-4;
+                let dx = (self.field.at((x + e) * f, y * f, t) - self.field.at((x - e) * f, y * f, t)) / (2.0 * e * f);
+                let dy = (self.field.at(x * f, (y + e) * f, t) - self.field.at(x * f, (y - e) * f, t)) / (2.0 * e * f);
+                (dy, -dx)
+            } else {
+                let a = self.field.at(x * f, y * f, t) * TWOPI;
+                (a.cos(), a.sin())
+            }
+        }
+    }
+
+    impl NoiseModel for Flow {
+        fn step(&mut self, path: &Array2<f64>, i: usize, _rng: &mut StdRng) -> Array2<f64> {
+            // coherent steps add up linearly, so they grow like the random walk's spread (~i^1.5)
+            // rather than like its step size (~i)
+            let grow = (i as f64 + 1.0).sqrt();
+            self.noise = self.scale.map(|s| 2.0 * s.abs() * grow);
+            let t = i as f64 * TIME_STEP;
+            let mut d = Array2::<f64>::zeros(path.raw_dim());
+            for (k, p) in path.rows().into_iter().enumerate() {
+                let (dx, dy) = self.direction(p[0], p[1], t);
+                d[[k, 0]] = dx * self.noise[k];
+                d[[k, 1]] = dy * self.noise[k];
+            }
+            d
+        }
+
+        fn amplitude(&self) -> &Array1<f64> {
+            &self.noise
+        }
+    }
+
+    pub struct MeanReverting {
+        walk: RandomWalk,
+        origin: Array2<f64>,
+        theta: f64,
+    }
+
+    impl NoiseModel for MeanReverting {
+        fn step(&mut self, path: &Array2<f64>, i: usize, rng: &mut StdRng) -> Array2<f64> {
+            self.walk.noise = self.walk.noise.map(|n| n * (1.0 - self.theta));
+            let d = self.walk.step(path, i, rng);
+            d + (&self.origin - path).mul(self.theta)
+        }
+
+        fn amplitude(&self) -> &Array1<f64> {
+            self.walk.amplitude()
+        }
+    }
+
+    /// Ken Perlin's improved gradient noise, roughly within -1..1, with a permutation from the rng.
+    pub struct Perlin {
+        perm: Vec<usize>,
+    }
+
+    impl Perlin {
+        pub fn new(rng: &mut StdRng) -> Perlin {
+            let mut perm: Vec<usize> = (0..256).collect();
+            perm.shuffle(rng);
+            perm.extend_from_within(..);
+            Perlin { perm }
+        }
+
+        pub fn at(&self, x: f64, y: f64, z: f64) -> f64 {
+            let (xf, yf, zf) = (x.floor(), y.floor(), z.floor());
+            let (xi, yi, zi) = ((xf as i64 & 255) as usize, (yf as i64 & 255) as usize, (zf as i64 & 255) as usize);
+            let (x, y, z) = (x - xf, y - yf, z - zf);
+            let (u, v, w) = (fade(x), fade(y), fade(z));
+            let p = &self.perm;
+            let (a, b) = (p[xi] + yi, p[xi + 1] + yi);
+            let (aa, ab, ba, bb) = (p[a] + zi, p[a + 1] + zi, p[b] + zi, p[b + 1] + zi);
+            lerp(w,
+                lerp(v,
+                    lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z)),
+                    lerp(u, grad(p[ab], x, y - 1.0, z), grad(p[bb], x - 1.0, y - 1.0, z))),
+                lerp(v,
+                    lerp(u, grad(p[aa + 1], x, y, z - 1.0), grad(p[ba + 1], x - 1.0, y, z - 1.0)),
+                    lerp(u, grad(p[ab + 1], x, y - 1.0, z - 1.0), grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0))))
+        }
+    }
+
+    fn fade(t: f64) -> f64 {
+        t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+    }
+
+    fn lerp(t: f64, a: f64, b: f64) -> f64 {
+        a + t * (b - a)
+    }
+
+    fn grad(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+        let h = hash & 15;
+        let u = if h < 8 { x } else { y };
+        let v = if h < 4 { y } else if h == 12 || h == 14 { x } else { z };
+        (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+    }
+}
@@ -0,0 +1,302 @@
+pub mod plot {
+    use std::fs::File;
+    use std::io::{self, BufWriter, Write};
+    use std::path::Path;
+    use ndarray::Array2;
+
+    use crate::screen::screen::{Argb, Gradient};
+    use crate::sink::sink::SampleSink;
+
+    type Point = (f64, f64);
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum PlotFormat {
+        Gcode,
+        Hpgl,
+    }
+
+    impl PlotFormat {
+        /// `.gcode`, `.nc` and `.ngc` are G-code, `.hpgl` and `.plt` are HPGL.
+        pub fn from_path(path: &str) -> Option<PlotFormat> {
+            let ext = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+            match ext.as_str() {
+                "gcode" | "nc" | "ngc" => Some(PlotFormat::Gcode),
+                "hpgl" | "plt" => Some(PlotFormat::Hpgl),
+                _ => None,
+            }
+        }
+    }
+
+    /// Physical page in millimeters; the drawing is scaled to fit inside the margins, keeping its
+    /// aspect ratio, and centered.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Page {
+        pub width: f64,
+        pub height: f64,
+        pub margin: f64,
+    }
+
+    // the margin comes from --margin
+    impl Page {
+        pub const A3: Page = Page { width: 297.0, height: 420.0, margin: 0.0 };
+        pub const A4: Page = Page { width: 210.0, height: 297.0, margin: 0.0 };
+        pub const A5: Page = Page { width: 148.0, height: 210.0, margin: 0.0 };
+        pub const LETTER: Page = Page { width: 215.9, height: 279.4, margin: 0.0 };
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct PlotOpts {
+        pub format: PlotFormat,
+        pub page: Page,
+        /// G-code lines that lift and lower the pen
+        pub pen_up: String,
+        pub pen_down: String,
+        /// Drawing speed in mm/min (G-code)
+        pub feed: f64,
+    }
+
+    /// Collects every batch as a stroke, `[x, y]` only, remembering which layer it belongs to so
+    /// that layers can go to different pens.
+    #[derive(Default)]
+    pub struct Strokes {
+        pub paths: Vec<(usize, Vec<Point>)>,
+        layer: Option<usize>,
+    }
+
+    impl SampleSink for Strokes {
+        fn send(&mut self, samples: Array2<f64>) {
+            let path: Vec<Point> = samples.rows().into_iter().map(|r| (r[0], r[1])).collect();
+            if path.len() > 1 {
+                self.paths.push((self.layer.unwrap_or(0), path));
+            }
+        }
+
+        fn set_style(&mut self, _color: Argb, _gradient: Option<&Gradient>) {
+            self.layer = Some(self.layer.map_or(0, |l| l + 1));
+        }
+    }
+
+    fn dist(a: Point, b: Point) -> f64 {
+        (a.0 - b.0).hypot(a.1 - b.1)
+    }
+
+    /// Distance travelled with the pen up, starting from the origin.
+    pub fn travel(paths: &[(usize, Vec<Point>)]) -> f64 {
+        let mut at = (0.0, 0.0);
+        let mut total = 0.0;
+        for (_, p) in paths {
+            total += dist(at, p[0]);
+            at = p[p.len() - 1];
+        }
+        total
+    }
+
+    /// Reorders (and reverses, where that helps) the strokes of each layer so that the pen always
+    /// continues with the stroke end nearest to where it is. Greedy, but it removes most of the
+    /// back and forth of drawing strokes in generation order; when it does not, the strokes are
+    /// left in that order.
+    pub fn optimize(paths: &mut Vec<(usize, Vec<Point>)>) {
+        // stable, so layers keep their order
+        paths.sort_by_key(|(layer, _)| *layer);
+        let before = travel(paths);
+        let mut left = paths.clone();
+        let mut ordered = Vec::with_capacity(left.len());
+        let mut at = (0.0, 0.0);
+        while !left.is_empty() {
+            let layer = left[0].0;
+            let mut best = (0, false, f64::INFINITY);
+            for (i, (l, p)) in left.iter().enumerate() {
+                if *l != layer {
+                    break;
+                }
+                let (d_start, d_end) = (dist(at, p[0]), dist(at, p[p.len() - 1]));
+                if d_start < best.2 {
+                    best = (i, false, d_start);
+                }
+                if d_end < best.2 {
+                    best = (i, true, d_end);
+                }
+            }
+            let (l, mut p) = left.remove(best.0);
+            if best.1 {
+                p.reverse();
+            }
+            at = p[p.len() - 1];
+            ordered.push((l, p));
+        }
+        if travel(&ordered) < before {
+            *paths = ordered;
+        }
+    }
+
+    /// Maps normalized strokes onto `page` in millimeters, origin at the bottom left and y up as
+    /// plotters expect, dropping points closer than `tolerance` mm to the previous one.
+    pub fn fit(paths: &[(usize, Vec<Point>)], page: &Page, tolerance: f64) -> Vec<(usize, Vec<Point>)> {
+        let points = paths.iter().flat_map(|(_, p)| p.iter());
+        let (mut x0, mut y0, mut x1, mut y1) = (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for &(x, y) in points {
+            x0 = x0.min(x);
+            y0 = y0.min(y);
+            x1 = x1.max(x);
+            y1 = y1.max(y);
+        }
+        let (w, h) = ((x1 - x0).max(1e-9), (y1 - y0).max(1e-9));
+        let scale = ((page.width - 2.0 * page.margin) / w).min((page.height - 2.0 * page.margin) / h);
+        let left = (page.width - w * scale) / 2.0;
+        let top = (page.height - h * scale) / 2.0;
+
+        paths.iter().map(|(l, p)| {
+            let mut out: Vec<Point> = Vec::with_capacity(p.len());
+            for (i, &(x, y)) in p.iter().enumerate() {
+                let q = (left + (x - x0) * scale, page.height - top - (y - y0) * scale);
+                let near = out.last().is_some_and(|&last| dist(last, q) < tolerance);
+                if !near || i + 1 == p.len() {
+                    out.push(q);
+                }
+            }
+            (*l, out)
+        }).collect()
+    }
+
+    pub fn write_gcode<W: Write>(out: &mut W, paths: &[(usize, Vec<Point>)], opts: &PlotOpts) -> io::Result<()> {
+        writeln!(out, "; {} strokes on a {}x{} mm page", paths.len(), opts.page.width, opts.page.height)?;
+        writeln!(out, "G21\nG90")?;
+        writeln!(out, "{}", opts.pen_up)?;
+        let mut layer = paths.first().map_or(0, |(l, _)| *l);
+        for (l, p) in paths {
+            if *l != layer {
+                writeln!(out, "G0 X0 Y0\nM0 ; change pen for layer {}", l)?;
+                layer = *l;
+            }
+            writeln!(out, "G0 X{:.3} Y{:.3}", p[0].0, p[0].1)?;
+            writeln!(out, "{}", opts.pen_down)?;
+            writeln!(out, "G1 F{}", opts.feed)?;
+            for (x, y) in &p[1..] {
+                writeln!(out, "G1 X{:.3} Y{:.3}", x, y)?;
+            }
+            writeln!(out, "{}", opts.pen_up)?;
+        }
+        writeln!(out, "G0 X0 Y0\nM2")
+    }
+
+    // HPGL plotter units per millimeter
+    const HPGL_UNITS: f64 = 40.0;
+
+    pub fn write_hpgl<W: Write>(out: &mut W, paths: &[(usize, Vec<Point>)]) -> io::Result<()> {
+        let unit = |v: f64| (v * HPGL_UNITS).round() as i64;
+        write!(out, "IN;")?;
+        let mut layer = None;
+        for (l, p) in paths {
+            if layer != Some(*l) {
+                // pens are numbered from 1
+                write!(out, "\nSP{};", l + 1)?;
+                layer = Some(*l);
+            }
+            write!(out, "\nPU{},{};PD", unit(p[0].0), unit(p[0].1))?;
+            for (i, (x, y)) in p[1..].iter().enumerate() {
+                write!(out, "{}{},{}", if i == 0 { "" } else { "," }, unit(*x), unit(*y))?;
+            }
+            write!(out, ";")?;
+        }
+        writeln!(out, "\nPU;SP0;")
+    }
+
+    /// Optimizes, fits and writes `strokes` to `path` in `opts.format`; returns the pen-up travel
+    /// in mm before and after optimizing.
+    pub fn save(strokes: Strokes, path: &str, opts: &PlotOpts) -> io::Result<(f64, f64)> {
+        let page = &opts.page;
+        if !(page.margin >= 0.0 && page.width > 2.0 * page.margin && page.height > 2.0 * page.margin) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                format!("a {}x{} mm page has no room inside {} mm margins", page.width, page.height, page.margin)));
+        }
+        let mut paths = fit(&strokes.paths, &opts.page, 0.1);
+        let before = travel(&paths);
+        optimize(&mut paths);
+        let after = travel(&paths);
+
+        let mut out = BufWriter::new(File::create(path)?);
+        match opts.format {
+            PlotFormat::Gcode => write_gcode(&mut out, &paths, opts)?,
+            PlotFormat::Hpgl => write_hpgl(&mut out, &paths)?,
+        }
+        out.flush()?;
+        Ok((before, after))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use rand::Rng;
+        use crate::spl::spl::seeded;
+
+        fn strokes(seed: u64) -> Vec<(usize, Vec<Point>)> {
+            let mut rng = seeded(seed);
+            (0..40).map(|i| {
+                let n = rng.gen_range(2..6);
+                (i / 15, (0..n).map(|_| (rng.gen_range(0.0..100.0), rng.gen_range(0.0..100.0))).collect())
+            }).collect()
+        }
+
+        #[test]
+        fn optimize_does_not_add_travel() {
+            for seed in 0..20 {
+                let mut paths = strokes(seed);
+                let before = travel(&paths);
+                optimize(&mut paths);
+                assert!(travel(&paths) <= before, "seed {}", seed);
+            }
+        }
+
+        #[test]
+        fn optimize_keeps_every_stroke_once() {
+            let original = strokes(3);
+            let mut paths = original.clone();
+            optimize(&mut paths);
+            assert_eq!(paths.len(), original.len());
+            let mut used = vec![false; original.len()];
+            for (l, p) in &paths {
+                let reversed: Vec<Point> = p.iter().rev().copied().collect();
+                let i = (0..original.len())
+                    .find(|&i| !used[i] && original[i].0 == *l && (original[i].1 == *p || original[i].1 == reversed))
+                    .expect("stroke not in the input");
+                used[i] = true;
+            }
+            // layers stay in order
+            assert!(paths.windows(2).all(|w| w[0].0 <= w[1].0));
+        }
+
+        #[test]
+        fn optimize_reverses_strokes_that_end_nearer() {
+            let mut paths = vec![(0, vec![(0.0, 0.0), (1.0, 0.0)]), (0, vec![(5.0, 0.0), (2.0, 0.0)])];
+            optimize(&mut paths);
+            assert_eq!(paths, vec![(0, vec![(0.0, 0.0), (1.0, 0.0)]), (0, vec![(2.0, 0.0), (5.0, 0.0)])]);
+            assert_eq!(travel(&paths), 1.0);
+        }
+
+        #[test]
+        fn fit_centers_and_scales_into_the_margins() {
+            let page = Page { margin: 10.0, ..Page::A4 };
+            let paths = vec![(0, vec![(0.0, 0.0), (1.0, 1.0)]), (1, vec![(0.5, 0.0), (0.5, 1.0)])];
+            // 190 mm wide, the narrow side of the page minus margins, (297 - 190) / 2 above and below
+            let fitted = fit(&paths, &page, 0.1);
+            assert_eq!(fitted, vec![
+                (0, vec![(10.0, 243.5), (200.0, 53.5)]),
+                (1, vec![(105.0, 243.5), (105.0, 53.5)]),
+            ]);
+
+            // a wide drawing is limited by the width and keeps its aspect ratio
+            let fitted = fit(&[(0, vec![(-2.0, 3.0), (2.0, 4.0)])], &page, 0.1);
+            assert_eq!(fitted, vec![(0, vec![(10.0, 172.25), (200.0, 124.75)])]);
+        }
+
+        #[test]
+        fn fit_drops_points_within_tolerance_but_keeps_the_end() {
+            let page = Page { width: 64.0, height: 64.0, margin: 0.0 };
+            // 1/1024 is 0.0625 mm on this page
+            let step = 1.0 / 1024.0;
+            let path = vec![(0.0, 0.0), (step, 0.0), (1.5 * step, 0.0), (1.0, 1.0), (1.0, 1.0 - step)];
+            let fitted = fit(&[(0, path)], &page, 0.1);
+            assert_eq!(fitted, vec![(0, vec![(0.0, 64.0), (64.0, 0.0), (64.0, 0.0625)])]);
+        }
+    }
+}
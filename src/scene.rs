@@ -0,0 +1,317 @@
+pub mod scene {
+    use std::{fmt, fs, io, thread};
+    use std::collections::BTreeMap;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc, Mutex};
+    use rand::rngs::StdRng;
+    use serde::{Deserialize, Serialize};
+
+    use crate::screen::screen::{self, Screen, BlendMode, Gradient, Splat, ToneMap, set_blend, set_bg, set_splat, set_tone_map};
+    use crate::curve::curve::CurveKind;
+    use crate::interp::interp::Interpolation;
+    use crate::sink::sink::SampleSink;
+    use crate::job::job::{Progress, Tracked};
+    use crate::spl::spl::{self, CircleSplineOpts, ComplexEquationParams, HieroglyphOpts, ScriptOpts};
+
+    /// A whole artwork: canvas, background and the layers painted on it in order.
+    ///
+    /// ```toml
+    /// width = 1000
+    /// height = 1000
+    /// bg = [1.0, 0.0, 0.0, 0.0]
+    ///
+    /// [[layers]]
+    /// generator = "complex"
+    /// color = [0.2, 0.6, 1.0, 0.5]
+    /// seed = 42
+    /// stp = 0.000002
+    /// inum = 200
+    /// pnum_from = 100
+    /// pnum_to = 151
+    /// iterations = 80
+    /// params = [[3.1, 1.0, 0.01], [3.0, -3.0, 0.01], [0.5, 0.5, 0.01]]
+    ///
+    /// [layers.gradient]
+    /// colors = [[0.2, 0.6, 1.0, 0.5], [0.2, 1.0, 0.3, 0.6]]
+    /// by = "iteration"
+    /// ```
+    #[derive(Clone, Serialize, Deserialize)]
+    pub struct Scene {
+        #[serde(default = "default_size")]
+        pub width: usize,
+        #[serde(default = "default_size")]
+        pub height: usize,
+        pub bg: [f64; 4],
+        /// `"point"`, `"bilinear"` or `{ gaussian = <sigma in pixels> }`
+        #[serde(default)]
+        pub splat: Splat,
+        /// `"over"`, `"additive"`, `"screen"`, `"multiply"`, `"max"`, `"subtract"` or `"erase"`
+        #[serde(default)]
+        pub blend: BlendMode,
+        /// HDR accumulation: `"linear"`, `"log"`, `{ gamma = 2.2 }` or `{ exposure = 1.0 }`;
+        /// omitted composites every sample directly
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub tone: Option<ToneMap>,
+        /// Worker threads for layers that draw several splines; all cores when missing
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub threads: Option<usize>,
+        /// Seed for layers without their own; drawn at random when missing
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub seed: Option<u64>,
+        pub layers: Vec<Layer>,
+    }
+
+    #[derive(Clone, Serialize, Deserialize)]
+    pub struct Layer {
+        pub color: [f64; 4],
+        /// Overrides `color` per sample; `by` is `"position"`, `"iteration"` or `"noise"`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub gradient: Option<Gradient>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub seed: Option<u64>,
+        /// How control points become curves, e.g. `{ kind = "centripetal", tension = 0.5 }`;
+        /// each generator's original spline when missing
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub interpolation: Option<Interpolation>,
+        #[serde(flatten)]
+        pub generator: Generator,
+    }
+
+    #[derive(Clone, Serialize, Deserialize)]
+    #[serde(tag = "generator", rename_all = "kebab-case")]
+    pub enum Generator {
+        /// `count` circle splines drawn by parallel threads
+        Circle {
+            #[serde(default = "default_circles")]
+            count: usize,
+            #[serde(flatten)]
+            opts: CircleSplineOpts,
+        },
+        Complex {
+            #[serde(flatten)]
+            opts: CircleSplineOpts,
+            params: Vec<[f64; 3]>,
+            /// Phase of every term in radians, e.g. from `complex --outline`; none when empty
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            phases: Vec<f64>,
+        },
+        ComplexCircle {
+            params: Vec<[f64; 3]>,
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            phases: Vec<f64>,
+        },
+        /// Evolution of a built-in curve family, e.g. `curve = { kind = "rose", n = 7, d = 3 }`
+        Curve {
+            #[serde(flatten)]
+            opts: CircleSplineOpts,
+            #[serde(default)]
+            curve: CurveKind,
+            /// Width of the curve relative to the canvas side
+            #[serde(default = "default_curve_size")]
+            size: f64,
+        },
+        RandSplines,
+        Script(ScriptOpts),
+        Hieroglyphs(HieroglyphOpts),
+    }
+
+    impl Generator {
+        /// How many batches (curves or spline iterations) the generator sends, for progress.
+        pub fn batches(&self) -> usize {
+            match self {
+                Generator::Circle { count, opts } => count * opts.iterations,
+                Generator::Complex { opts, .. } => opts.iterations,
+                Generator::ComplexCircle { .. } => 1,
+                Generator::Curve { opts, .. } => opts.iterations,
+                Generator::RandSplines => 700,
+                Generator::Script(opts) => opts.row_count,
+                Generator::Hieroglyphs(opts) => opts.row_count * opts.col_count,
+            }
+        }
+    }
+
+    fn default_size() -> usize {
+        1000
+    }
+
+    fn default_circles() -> usize {
+        3
+    }
+
+    fn default_curve_size() -> f64 {
+        0.8
+    }
+
+    #[derive(Debug)]
+    pub enum SceneError {
+        Io(io::Error),
+        Toml(String),
+        Json(serde_json::Error),
+    }
+
+    impl fmt::Display for SceneError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                SceneError::Io(e) => write!(f, "{}", e),
+                SceneError::Toml(e) => write!(f, "invalid TOML scene: {}", e),
+                SceneError::Json(e) => write!(f, "invalid JSON scene: {}", e),
+            }
+        }
+    }
+
+    impl std::error::Error for SceneError {}
+
+    fn is_json(path: &str) -> bool {
+        Path::new(path).extension().is_some_and(|e| e.eq_ignore_ascii_case("json"))
+    }
+
+    /// Reads a scene, as JSON for `*.json` and as TOML otherwise.
+    pub fn load(path: &str) -> Result<Scene, SceneError> {
+        let text = fs::read_to_string(path).map_err(SceneError::Io)?;
+        if is_json(path) {
+            serde_json::from_str(&text).map_err(SceneError::Json)
+        } else {
+            toml::from_str(&text).map_err(|e| SceneError::Toml(e.to_string()))
+        }
+    }
+
+    pub fn save(scene: &Scene, path: &str) -> Result<(), SceneError> {
+        let text = if is_json(path) {
+            serde_json::to_string_pretty(scene).map_err(SceneError::Json)?
+        } else {
+            toml::to_string(scene).map_err(|e| SceneError::Toml(e.to_string()))?
+        };
+        fs::write(path, text).map_err(SceneError::Io)
+    }
+
+    pub fn canvas(scene: &Scene) -> Arc<Mutex<Screen>> {
+        let s = screen::new(scene.width, scene.height);
+        let sm = Arc::new(Mutex::new(s));
+        set_tone_map(sm.clone(), scene.tone);
+        set_bg(sm.clone(), scene.bg);
+        set_splat(sm.clone(), scene.splat);
+        set_blend(sm.clone(), scene.blend);
+        sm
+    }
+
+    pub fn default_threads() -> usize {
+        thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    }
+
+    pub fn threads(scene: &Scene) -> usize {
+        scene.threads.unwrap_or_else(default_threads)
+    }
+
+    // layers without a seed fork one from the scene rng
+    fn layer_rng(layer: &Layer, rng: &mut StdRng) -> StdRng {
+        match layer.seed {
+            Some(seed) => spl::seeded(seed),
+            None => spl::fork(rng),
+        }
+    }
+
+    fn circle_rngs(count: usize, r: &mut StdRng) -> Vec<StdRng> {
+        (0..count).map(|_| spl::fork(r)).collect()
+    }
+
+    /// Sends the batches of one layer to `sink` on the calling thread, circles one after another.
+    pub fn draw_layer<S: SampleSink + ?Sized>(sink: &mut S, generator: &Generator, interp: Option<&Interpolation>, r: &mut StdRng) {
+        match generator {
+            Generator::Circle { count, opts } => {
+                for mut tr in circle_rngs(*count, r) {
+                    spl::fill_circle_splines(sink, opts.clone(), interp, &mut tr);
+                }
+            },
+            Generator::Complex { opts, params, phases } => {
+                let cc = ComplexEquationParams { params: params.clone(), phases: phases.clone() };
+                spl::fill_complex_spline(sink, opts.clone(), cc, interp, r);
+            },
+            Generator::ComplexCircle { params, phases } => {
+                spl::fill_complex_circle(sink, ComplexEquationParams { params: params.clone(), phases: phases.clone() });
+            },
+            Generator::Curve { opts, curve, size } => {
+                spl::fill_curve_spline(sink, opts.clone(), curve, *size, interp, r);
+            },
+            Generator::RandSplines => {
+                spl::fill_rand_splines(sink, interp, r);
+            },
+            Generator::Script(opts) => {
+                spl::fill_spline_script(sink, opts.clone(), interp, r);
+            },
+            Generator::Hieroglyphs(opts) => {
+                spl::fill_spline_hieroglyph(sink, opts.clone(), interp, r);
+            },
+        }
+    }
+
+    // spacing is given in pixels of the scene canvas
+    fn layer_interp(scene: &Scene, layer: &Layer) -> Option<Interpolation> {
+        layer.interpolation.as_ref().map(|i| i.on_canvas(scene.width, scene.height))
+    }
+
+    /// Sends every layer to `sink` in order on the calling thread, e.g. to write a vector file.
+    /// The samples are the same as those `draw` paints for the same `rng`.
+    pub fn draw_into<S: SampleSink + ?Sized>(sink: &mut S, scene: &Scene, rng: &mut StdRng) {
+        for layer in scene.layers.iter() {
+            let mut r = layer_rng(layer, rng);
+            sink.set_style(layer.color, layer.gradient.as_ref());
+            draw_layer(sink, &layer.generator, layer_interp(scene, layer).as_ref(), &mut r);
+        }
+    }
+
+    /// Paints every layer onto `screen`, each finished before the next starts, counting batches
+    /// in `progress` and stopping early when it is cancelled. Layers without a seed fork one from `rng`.
+    pub fn draw(screen: Arc<Mutex<Screen>>, scene: &Scene, rng: &mut StdRng, progress: &Arc<Progress>) {
+        let threads = threads(scene);
+        let mut sink = Tracked::new(screen.clone(), progress.clone());
+        for layer in scene.layers.iter() {
+            if progress.is_cancelled() {
+                return;
+            }
+            let mut r = layer_rng(layer, rng);
+            let interp = layer_interp(scene, layer);
+            sink.set_style(layer.color, layer.gradient.as_ref());
+
+            match &layer.generator {
+                Generator::Circle { count, opts } => {
+                    // `threads` workers take circles off a shared counter, each drawing into its own
+                    // buffer; a finished circle is merged as soon as all earlier ones are, so the
+                    // result depends neither on timing nor on the thread count
+                    let rngs = circle_rngs(*count, &mut r);
+                    let next = AtomicUsize::new(0);
+                    let (tx, rx) = mpsc::channel();
+                    thread::scope(|s| {
+                        for _ in 0..threads.clamp(1, rngs.len().max(1)) {
+                            let tx = tx.clone();
+                            let (rngs, next, interp, screen) = (&rngs, &next, &interp, &screen);
+                            s.spawn(move || loop {
+                                let i = next.fetch_add(1, Ordering::Relaxed);
+                                if i >= rngs.len() || progress.is_cancelled() {
+                                    break;
+                                }
+                                let mut local = Tracked::new(screen.lock().unwrap().worker(), progress.clone());
+                                spl::fill_circle_splines(&mut local, opts.clone(), interp.as_ref(), &mut rngs[i].clone());
+                                if tx.send((i, local.inner)).is_err() {
+                                    break;
+                                }
+                            });
+                        }
+                        drop(tx);
+
+                        let mut pending = BTreeMap::new();
+                        let mut merged = 0;
+                        for (i, local) in rx {
+                            pending.insert(i, local);
+                            while let Some(local) = pending.remove(&merged) {
+                                screen.lock().unwrap().merge(&local);
+                                merged += 1;
+                            }
+                        }
+                    });
+                },
+                g => draw_layer(&mut sink, g, interp.as_ref(), &mut r),
+            }
+        }
+    }
+}
@@ -1,173 +1,627 @@
 pub mod screen {
-    extern crate minifb;
-    use std::{thread::sleep, time::Duration};
+    use std::fs::File;
+    use std::io::BufWriter;
     use std::usize;
-    use minifb::{Key, Window, WindowOptions};
     use std::sync::{Arc, Mutex};
+    use serde::{Deserialize, Serialize};
+
+    /// A color as `[a, r, g, b]`, each channel in 0..1, sRGB encoded, straight (not premultiplied) alpha.
+    /// This is the order of the packed pixel, see `Screen`.
+    pub type Argb = [f64; 4];
+
+    /// Canvas of `width` x `height` pixels.
+    ///
+    /// `buffer` is row-major (`buffer[y * width + x]`, y pointing down) and each pixel is packed
+    /// as `0xAARRGGBB`: minifb's `0RGB` layout with alpha in the top byte, which minifb ignores.
+    /// Pixels hold premultiplied sRGB values and blending happens on the encoded values, so the
+    /// window shows the canvas composited over black; `save_png` converts to straight alpha.
+    ///
+    /// Samples are in normalized coordinates: x to the right, y down, and the unit square maps
+    /// onto the largest centered square of the canvas so shapes keep their aspect ratio.
     pub struct Screen {
         width: usize,
         height: usize,
         buffer: Vec<u32>,
-        f64rgba: [f64; 4],
-        rgba: [u8; 4],
+        // current color, premultiplied
+        argb: Argb,
+        // per sample colors instead of `argb`, straight
+        gradient: Option<Gradient>,
+        splat: Splat,
+        blend: BlendMode,
+        // straight
+        bg: Argb,
+        // HDR mode: per pixel sums of weighted premultiplied samples [a, r, g, b],
+        // tone mapped into `buffer` by `resolve`; empty while compositing directly
+        density: Vec<[f64; 4]>,
+        tone: Option<ToneMap>,
+        dirty: bool,
+    }
+
+    /// How accumulated density becomes coverage in HDR mode. `D` is a pixel's summed sample alpha.
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum ToneMap {
+        /// `D / max D`
+        Linear,
+        /// `ln(1 + D / min D) / ln(1 + max D / min D)`, i.e. log of hit count; keeps faint grain visible
+        Log,
+        /// `(D / max D) ^ (1 / gamma)`
+        Gamma(f64),
+        /// `1 - exp(-exposure * D)`, unnormalized; 1.0 matches direct "over" compositing without its rounding
+        Exposure(f64),
+    }
+
+    /// How `send_buf` spreads one sample's alpha over pixels.
+    #[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Splat {
+        /// All of it on the pixel the sample falls into: grainy, aliased, resolution-dependent
+        #[default]
+        Point,
+        /// Shared among the four nearest pixel centers by distance
+        Bilinear,
+        /// Normalized Gaussian kernel, sigma in pixels
+        Gaussian(f64),
+    }
+
+    /// Colors evenly spaced over 0..1, linearly interpolated; `by` picks what moves along it.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Gradient {
+        pub colors: Vec<Argb>,
+        #[serde(default)]
+        pub by: GradientBy,
+    }
+
+    /// Which sample column indexes the gradient, see `send_buf`.
+    #[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum GradientBy {
+        /// Arc length position along the curve
+        #[default]
+        Position,
+        /// Evolution step of the spline
+        Iteration,
+        /// Accumulated noise magnitude around the sample
+        Noise,
+    }
+
+    impl GradientBy {
+        /// Index of the sample column, see `Screen::draw`.
+        pub fn column(&self) -> usize {
+            match self {
+                GradientBy::Position => 2,
+                GradientBy::Iteration => 3,
+                GradientBy::Noise => 4,
+            }
+        }
+    }
+
+    impl Gradient {
+        pub fn at(&self, t: f64) -> Argb {
+            let n = self.colors.len();
+            if n == 1 {
+                return self.colors[0];
+            }
+            let f = t.clamp(0.0, 1.0) * (n - 1) as f64;
+            let i = (f.floor() as usize).min(n - 2);
+            let u = f - i as f64;
+            let (a, b) = (&self.colors[i], &self.colors[i + 1]);
+            [
+                a[0] + (b[0] - a[0]) * u,
+                a[1] + (b[1] - a[1]) * u,
+                a[2] + (b[2] - a[2]) * u,
+                a[3] + (b[3] - a[3]) * u,
+            ]
+        }
+    }
+
+    /// How a sample (or in HDR mode the tone mapped image) is combined with what is already on the
+    /// canvas. Works on premultiplied values, results are clamped to 0..1.
+    #[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum BlendMode {
+        /// Porter-Duff source over destination
+        #[default]
+        Over,
+        /// Sum of both, for glowing light trails on dark backgrounds
+        Additive,
+        /// `1 - (1 - s)(1 - d)`: brightens like additive but never blows out
+        Screen,
+        /// Product of both, for ink on light paper
+        Multiply,
+        /// Brightest of both, per channel
+        Max,
+        /// Takes the color away from the destination, alpha unchanged
+        Subtract,
+        /// Takes the alpha away from the destination, cutting holes into the background
+        Erase,
+    }
+
+    impl BlendMode {
+        // how a worker buffer collects samples so that merging it with `self` afterwards
+        // matches compositing them one by one
+        fn accumulate(&self) -> BlendMode {
+            match self {
+                BlendMode::Subtract => BlendMode::Additive,
+                BlendMode::Erase => BlendMode::Over,
+                b => *b,
+            }
+        }
+
+        // `s` and `d` are premultiplied [a, r, g, b]
+        fn composite(&self, s: &Argb, d: &Argb) -> Argb {
+            let mut out = [0.0; 4];
+            for k in 0..4 {
+                out[k] = match self {
+                    BlendMode::Over => s[k] + d[k] * (1.0 - s[0]),
+                    BlendMode::Additive => s[k] + d[k],
+                    BlendMode::Screen => s[k] + d[k] - s[k] * d[k],
+                    BlendMode::Multiply if k == 0 => s[0] + d[0] - s[0] * d[0],
+                    BlendMode::Multiply => s[k] * d[k] + s[k] * (1.0 - d[0]) + d[k] * (1.0 - s[0]),
+                    BlendMode::Max => s[k].max(d[k]),
+                    BlendMode::Subtract if k == 0 => d[0],
+                    BlendMode::Subtract => d[k] - s[k],
+                    BlendMode::Erase => d[k] * (1.0 - s[0]),
+                }.clamp(0.0, 1.0);
+            }
+            out
+        }
     }
 
     pub fn new (w: usize, h: usize) -> Screen {
-        let f64rgba = [0.0, 0.0, 0.0, 0.0];
         Screen{
             width: w,
             height: h,
             buffer: vec![0; w * h],
-            f64rgba,
-            rgba: as_u8(&f64rgba),
+            argb: [0.0, 0.0, 0.0, 0.0],
+            gradient: None,
+            splat: Splat::Point,
+            blend: BlendMode::Over,
+            bg: [0.0, 0.0, 0.0, 0.0],
+            density: Vec::new(),
+            tone: None,
+            dirty: false,
         }
     }
     
-    pub fn set_color(screen: Arc<Mutex<Screen>>, f64rgba: [f64; 4]) {
+    pub fn set_color(screen: Arc<Mutex<Screen>>, argb: Argb) {
         let m  = Arc::clone(&screen);
         let mut s = m.lock().unwrap();
+        s.argb = premultiply(&argb);
+        std::mem::drop(s);
+    }
 
-        s.f64rgba[0] = f64rgba[0];
-        s.f64rgba[1] = f64rgba[1] * f64rgba[0];
-        s.f64rgba[2] = f64rgba[2] * f64rgba[0];
-        s.f64rgba[3] = f64rgba[3] * f64rgba[0];
-        s.rgba = as_u8(&s.f64rgba);
+    /// `Some` colors every sample from the gradient, `None` goes back to the `set_color` color.
+    pub fn set_gradient(screen: Arc<Mutex<Screen>>, gradient: Option<Gradient>) {
+        let m  = Arc::clone(&screen);
+        let mut s = m.lock().unwrap();
+        s.gradient = gradient;
+    }
 
-        std::mem::drop(s);
+    /// Clears the canvas to `argb`; use alpha 1.0 for an opaque background.
+    pub fn set_bg(screen: Arc<Mutex<Screen>>, argb: Argb) {
+        let m  = Arc::clone(&screen);
+        let mut s = m.lock().unwrap();
+        s.buffer.fill(pack_argb(&as_u8(&premultiply(&argb))));
+        s.bg = argb;
+        s.density.fill([0.0; 4]);
     }
 
-    pub fn set_bg(screen: Arc<Mutex<Screen>>, f64rgba: [f64; 4]) {
+    /// `Some` switches the screen to HDR accumulation, `None` back to compositing every sample
+    /// straight into the 8-bit buffer. Call before drawing: accumulated density is discarded.
+    pub fn set_tone_map(screen: Arc<Mutex<Screen>>, tone: Option<ToneMap>) {
         let m  = Arc::clone(&screen);
         let mut s = m.lock().unwrap();
-        let rgba = as_u8(&f64rgba);
-        s.buffer.fill(as_u32_be(&rgba));
+        s.tone = tone;
+        s.density = match tone {
+            Some(_) => vec![[0.0; 4]; s.width * s.height],
+            None => Vec::new(),
+        };
+        s.dirty = tone.is_some();
     }
 
-    pub fn send_buf(screen: Arc<Mutex<Screen>>, xys: ndarray::Array2<f64>) {
+    pub fn set_splat(screen: Arc<Mutex<Screen>>, splat: Splat) {
         let m  = Arc::clone(&screen);
         let mut s = m.lock().unwrap();
-        for row in xys.rows().into_iter() {
-            if !(row[0] > 1.0 || row[0] < 0.0 || row[1] > 1.0 || row[1] < 0.0) {
-                let xf64 = row[0];
-                let yf64 = row[1];
-                
-                let mut x = (s.width as f64 * xf64) as usize;
-                if x >= s.width {
-                    x = s.width-1;
-                }
-                let mut y = (s.height as f64 * yf64) as usize;
-                if y >= s.height {
-                    y = s.height-1;
-                }
-    
-                let mut current = as_f64(&u32_to_u8(s.buffer[x * s.width + y]));
-    
-                let invaa = 1.0 - s.f64rgba[0];
-                current[0] = s.f64rgba[0] + current[0] * invaa;
-                current[1] = s.f64rgba[1] + current[1] * invaa;
-                current[2] = s.f64rgba[2] + current[2] * invaa;
-                current[3] = s.f64rgba[3] + current[3] * invaa;
-    
-                let w = s.width;
-                s.buffer[x * w + y] = as_u32_be(&as_u8(&current));
-            }
-        }
+        s.splat = splat;
+    }
 
-        std::mem::drop(s);
+    /// Applies to samples sent from now on; in HDR mode to the whole image when it is resolved.
+    pub fn set_blend(screen: Arc<Mutex<Screen>>, blend: BlendMode) {
+        let m  = Arc::clone(&screen);
+        let mut s = m.lock().unwrap();
+        s.blend = blend;
+        s.dirty = s.tone.is_some();
     }
 
-    pub fn renderWithTimeout(screen: Arc<Mutex<Screen>>) {     
+    /// Locks the screen and draws one batch of samples, see `Screen::draw`.
+    pub fn send_buf(screen: Arc<Mutex<Screen>>, xys: ndarray::Array2<f64>) {
         let m  = Arc::clone(&screen);
-        let s = m.lock().unwrap();
-        let mut window = Window::new(
-            "Test - ESC to exit",
-            s.width,
-            s.height,
-            WindowOptions::default(),
-        )
-        .unwrap_or_else(|e| {
-            panic!("{}", e);
-        });
+        let mut s = m.lock().unwrap();
+        s.draw(&xys);
         std::mem::drop(s);
+    }
 
-        window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
-        while window.is_open() && !window.is_key_down(Key::Escape) {
-            {
-                let s = m.lock().unwrap();                
-                window
-                    .update_with_buffer(&s.buffer, s.width, s.height)
-                    .unwrap();
+    /// Normalized sample coordinates to fractional pixel coordinates on a `width` x `height` canvas.
+    pub fn to_pixel(width: usize, height: usize, x: f64, y: f64) -> (f64, f64) {
+        let side = width.min(height) as f64;
+        ((x - 0.5) * side + width as f64 / 2.0, (y - 0.5) * side + height as f64 / 2.0)
+    }
 
+    /// Cumulative arc length of `[x, y, ..]` rows, normalized to 0..1.
+    pub fn arc_length(xys: &ndarray::Array2<f64>) -> Vec<f64> {
+        let mut ts = Vec::<f64>::with_capacity(xys.nrows());
+        let mut total = 0.0;
+        for r in 0..xys.nrows() {
+            if r > 0 {
+                total += (xys[[r, 0]] - xys[[r - 1, 0]]).hypot(xys[[r, 1]] - xys[[r - 1, 1]]);
+            }
+            ts.push(total);
+        }
+        if total > 0.0 {
+            for t in ts.iter_mut() {
+                *t /= total;
             }
         }
+        ts
     }
 
-    pub fn render(screen: Arc<Mutex<Screen>>) {     
+    pub fn save_png(screen: Arc<Mutex<Screen>>, path: &str) -> Result<(), png::EncodingError> {
         let m  = Arc::clone(&screen);
-        let s = m.lock().unwrap();
-        let mut window = Window::new(
-            "Test - ESC to exit",
-            s.width,
-            s.height,
-            WindowOptions::default(),
-        )
-        .unwrap_or_else(|e| {
-            panic!("{}", e);
-        });
-        std::mem::drop(s);
+        let mut s = m.lock().unwrap();
+        s.save_png(path)
+    }
+
+    impl Screen {
+        /// Draws one batch of samples, typically one curve. Rows are `[x, y]`, optionally followed by
+        /// `[position, iteration, noise]` in 0..1 for gradients. When a gradient asks for a column the
+        /// rows do not have, the position along the batch is used instead.
+        pub fn draw(&mut self, xys: &ndarray::Array2<f64>) {
+            let ts = self.gradient.as_ref().map(|g| {
+                if xys.ncols() > g.by.column() {
+                    xys.column(g.by.column()).to_vec()
+                } else {
+                    arc_length(xys)
+                }
+            });
+            for (r, row) in xys.rows().into_iter().enumerate() {
+                let (xf64, yf64) = self.to_pixel(row[0], row[1]);
+                let color = match (&self.gradient, &ts) {
+                    (Some(g), Some(ts)) => premultiply(&g.at(ts[r])),
+                    _ => self.argb,
+                };
+
+                match self.splat {
+                    Splat::Point => {
+                        self.blend(xf64.floor() as i64, yf64.floor() as i64, &color, 1.0);
+                    },
+                    Splat::Bilinear => {
+                        // distances to the four surrounding pixel centers
+                        let fx = xf64 - 0.5;
+                        let fy = yf64 - 0.5;
+                        let x0 = fx.floor();
+                        let y0 = fy.floor();
+                        let tx = fx - x0;
+                        let ty = fy - y0;
+                        let (x0, y0) = (x0 as i64, y0 as i64);
+                        self.blend(x0, y0, &color, (1.0 - tx) * (1.0 - ty));
+                        self.blend(x0 + 1, y0, &color, tx * (1.0 - ty));
+                        self.blend(x0, y0 + 1, &color, (1.0 - tx) * ty);
+                        self.blend(x0 + 1, y0 + 1, &color, tx * ty);
+                    },
+                    Splat::Gaussian(sigma) => {
+                        let r = (3.0 * sigma).ceil().max(1.0) as i64;
+                        let cx = xf64.floor() as i64;
+                        let cy = yf64.floor() as i64;
+                        let mut kernel = Vec::<(i64, i64, f64)>::with_capacity(((2 * r + 1) * (2 * r + 1)) as usize);
+                        let mut total = 0.0;
+                        for x in cx - r..=cx + r {
+                            for y in cy - r..=cy + r {
+                                let dx = x as f64 + 0.5 - xf64;
+                                let dy = y as f64 + 0.5 - yf64;
+                                let w = (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp();
+                                total += w;
+                                kernel.push((x, y, w));
+                            }
+                        }
+                        for (x, y, w) in kernel {
+                            self.blend(x, y, &color, w / total);
+                        }
+                    },
+                }
+            }
+        }
+
+        /// An empty, transparent screen with the same size, color, splat and tone mapping, for a
+        /// worker thread to draw into without locking; `merge` it back when done.
+        pub fn worker(&self) -> Screen {
+            Screen {
+                width: self.width,
+                height: self.height,
+                buffer: vec![0; self.width * self.height],
+                argb: self.argb,
+                gradient: self.gradient.clone(),
+                splat: self.splat,
+                blend: self.blend.accumulate(),
+                bg: [0.0, 0.0, 0.0, 0.0],
+                density: vec![[0.0; 4]; self.density.len()],
+                tone: self.tone,
+                dirty: false,
+            }
+        }
 
-        window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
-        while window.is_open() && !window.is_key_down(Key::Escape) {
-            {
-                let s = m.lock().unwrap();                
-                window
-                    .update_with_buffer(&s.buffer, s.width, s.height)
-                    .unwrap();
+        /// Composites a `worker` screen onto this one with this screen's blend mode;
+        /// in HDR mode its density is added.
+        pub fn merge(&mut self, other: &Screen) {
+            if self.tone.is_some() {
+                for (d, o) in self.density.iter_mut().zip(other.density.iter()) {
+                    for k in 0..4 {
+                        d[k] += o[k];
+                    }
+                }
+                self.dirty = true;
+                return;
+            }
+            for (px, o) in self.buffer.iter_mut().zip(other.buffer.iter()) {
+                if *o == 0 {
+                    continue;
+                }
+                let blended = self.blend.composite(&as_f64(&unpack_argb(*o)), &as_f64(&unpack_argb(*px)));
+                *px = pack_argb(&as_u8(&blended));
+            }
+        }
 
+        /// A `width` x `height` copy, each pixel the average of the pixels it covers.
+        pub fn thumbnail(&self, width: usize, height: usize) -> Screen {
+            let mut t = new(width, height);
+            for ty in 0..height {
+                let (y0, y1) = (ty * self.height / height, ((ty + 1) * self.height / height).max(ty * self.height / height + 1));
+                for tx in 0..width {
+                    let (x0, x1) = (tx * self.width / width, ((tx + 1) * self.width / width).max(tx * self.width / width + 1));
+                    let mut sum = [0.0; 4];
+                    for y in y0..y1.min(self.height) {
+                        for x in x0..x1.min(self.width) {
+                            let px = as_f64(&unpack_argb(self.buffer[y * self.width + x]));
+                            for k in 0..4 {
+                                sum[k] += px[k];
+                            }
+                        }
+                    }
+                    let n = ((y1.min(self.height) - y0) * (x1.min(self.width) - x0)).max(1) as f64;
+                    for c in sum.iter_mut() {
+                        *c /= n;
+                    }
+                    t.buffer[ty * width + tx] = pack_argb(&as_u8(&sum));
+                }
             }
-            sleep(Duration::from_millis(500));
+            t
+        }
+
+        /// Copies `other` with its top left corner at pixel (x, y), clipped to this screen.
+        pub fn paste(&mut self, other: &Screen, x: usize, y: usize) {
+            for oy in 0..other.height.min(self.height.saturating_sub(y)) {
+                for ox in 0..other.width.min(self.width.saturating_sub(x)) {
+                    self.buffer[(y + oy) * self.width + x + ox] = other.buffer[oy * other.width + ox];
+                }
+            }
+        }
+
+        pub fn width(&self) -> usize {
+            self.width
+        }
+
+        pub fn height(&self) -> usize {
+            self.height
+        }
+
+        /// Packed `0xAARRGGBB` premultiplied pixels, row-major; see `Screen`.
+        pub fn buffer(&self) -> &[u32] {
+            &self.buffer
+        }
+
+        fn to_pixel(&self, x: f64, y: f64) -> (f64, f64) {
+            to_pixel(self.width, self.height, x, y)
+        }
+
+        // composites the premultiplied `color` at pixel (x, y) with its alpha scaled by `weight`;
+        // pixels outside the canvas are skipped
+        fn blend(&mut self, x: i64, y: i64, color: &Argb, weight: f64) {
+            if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+                return;
+            }
+            let i = y as usize * self.width + x as usize;
+            if self.tone.is_some() {
+                let d = &mut self.density[i];
+                for k in 0..4 {
+                    d[k] += color[k] * weight;
+                }
+                self.dirty = true;
+                return;
+            }
+            let current = as_f64(&unpack_argb(self.buffer[i]));
+            let mut sample = *color;
+            for c in sample.iter_mut() {
+                *c *= weight;
+            }
+
+            self.buffer[i] = pack_argb(&as_u8(&self.blend.composite(&sample, &current)));
+        }
+
+        /// Tone maps the HDR density into `buffer`, blended onto the background, if anything changed.
+        /// The color of a pixel is the alpha-weighted average of the samples that hit it.
+        pub fn resolve(&mut self) {
+            let tone = match self.tone {
+                Some(t) if self.dirty => t,
+                _ => return,
+            };
+
+            let mut max_d: f64 = 0.0;
+            let mut min_d = f64::INFINITY;
+            for d in self.density.iter() {
+                if d[0] > 0.0 {
+                    max_d = max_d.max(d[0]);
+                    min_d = min_d.min(d[0]);
+                }
+            }
+
+            let bg = premultiply(&self.bg);
+            for (i, d) in self.density.iter().enumerate() {
+                let coverage = if d[0] <= 0.0 {
+                    0.0
+                } else {
+                    match tone {
+                        ToneMap::Linear => d[0] / max_d,
+                        ToneMap::Log => (1.0 + d[0] / min_d).ln() / (1.0 + max_d / min_d).ln(),
+                        ToneMap::Gamma(g) => (d[0] / max_d).powf(1.0 / g),
+                        ToneMap::Exposure(e) => 1.0 - (-e * d[0]).exp(),
+                    }
+                }.min(1.0);
+
+                let mut px = [coverage, 0.0, 0.0, 0.0];
+                for k in 1..4 {
+                    let color = if d[0] > 0.0 { (d[k] / d[0]).min(1.0) } else { 0.0 };
+                    px[k] = color * coverage;
+                }
+                self.buffer[i] = pack_argb(&as_u8(&self.blend.composite(&px, &bg)));
+            }
+            self.dirty = false;
+        }
+
+        /// Writes the canvas to `path` as an 8-bit sRGB PNG with straight alpha, no window needed.
+        pub fn save_png(&mut self, path: &str) -> Result<(), png::EncodingError> {
+            self.resolve();
+            let file = File::create(path)?;
+            let mut encoder = png::Encoder::new(BufWriter::new(file), self.width as u32, self.height as u32);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            let mut data = Vec::<u8>::with_capacity(self.buffer.len() * 4);
+            for px in self.buffer.iter() {
+                let argb = unpack_argb(*px);
+                data.extend_from_slice(&unpremultiply_rgba(&argb));
+            }
+
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&data)
         }
     }
 
-    impl Screen {
+    fn premultiply(argb: &Argb) -> Argb {
+        [argb[0], argb[1] * argb[0], argb[2] * argb[0], argb[3] * argb[0]]
     }
 
-    fn as_u8(array: &[f64; 4]) -> [u8; 4] {
-        let r = (array[0] * 255.0) as u8;
-        let g = (array[1] * 255.0) as u8;
-        let b = (array[2] * 255.0) as u8;
-        let a = (array[3] * 255.0) as u8;
-        [r, g, b, a]
+    // packed premultiplied [a, r, g, b] to straight [r, g, b, a], as PNG stores it
+    fn unpremultiply_rgba(argb: &[u8; 4]) -> [u8; 4] {
+        let a = argb[0] as u32;
+        if a == 0 {
+            return [0, 0, 0, 0];
+        }
+        let c = |v: u8| ((v as u32 * 255 + a / 2) / a).min(255) as u8;
+        [c(argb[1]), c(argb[2]), c(argb[3]), argb[0]]
     }
 
+    // channels in 0..1 to bytes, same order
+    fn as_u8(array: &[f64; 4]) -> [u8; 4] {
+        let mut out = [0u8; 4];
+        for k in 0..4 {
+            out[k] = (array[k] * 255.0) as u8;
+        }
+        out
+    }
 
     fn as_f64(array: &[u8; 4]) -> [f64; 4] {
-        let r = array[0] as f64 / 255.0;
-        let g = array[1] as f64 / 255.0;
-        let b = array[2] as f64 / 255.0;
-        let a = array[3] as f64 / 255.0;
-        [r, g, b, a]
+        let mut out = [0.0; 4];
+        for k in 0..4 {
+            out[k] = array[k] as f64 / 255.0;
+        }
+        out
     }
 
-    fn as_u32_be(array: &[u8; 4]) -> u32 {
+    // [a, r, g, b] bytes to 0xAARRGGBB
+    fn pack_argb(array: &[u8; 4]) -> u32 {
         ((array[0] as u32) << 24) +
         ((array[1] as u32) << 16) +
         ((array[2] as u32) <<  8) +
         ((array[3] as u32) <<  0)
     }
 
-    // fn as_u32_le(array: &[u8; 4]) -> u32 {
-    //     ((array[0] as u32) <<  0) +
-    //     ((array[1] as u32) <<  8) +
-    //     ((array[2] as u32) << 16) +
-    //     ((array[3] as u32) << 24)
-    // }
-
-    fn u32_to_u8(x:u32) -> [u8;4] {
+    fn unpack_argb(x: u32) -> [u8; 4] {
         let b1 : u8 = ((x >> 24) & 0xff) as u8;
         let b2 : u8 = ((x >> 16) & 0xff) as u8;
         let b3 : u8 = ((x >> 8) & 0xff) as u8;
         let b4 : u8 = (x & 0xff) as u8;
         return [b1, b2, b3, b4]
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ndarray::arr2;
+        use std::io::BufReader;
+
+        fn shared(w: usize, h: usize) -> Arc<Mutex<Screen>> {
+            Arc::new(Mutex::new(new(w, h)))
+        }
+
+        #[test]
+        fn indexes_row_major_on_a_wide_canvas() {
+            // 7x3: the unit square maps to the centered 3x3 block starting at x = 2,
+            // so (1.1667, 0.8333) lands in the middle of pixel (5, 2)
+            let screen = shared(7, 3);
+            set_color(screen.clone(), [1.0, 1.0, 1.0, 1.0]);
+            let mut s = screen.lock().unwrap();
+            s.draw(&arr2(&[[3.5 / 3.0, 2.5 / 3.0]]));
+            for (i, px) in s.buffer().iter().enumerate() {
+                if i == 2 * 7 + 5 {
+                    assert_eq!(*px, 0xFFFFFFFF);
+                } else {
+                    assert_eq!(*px, 0, "pixel {} was touched", i);
+                }
+            }
+        }
+
+        #[test]
+        fn packs_rgb_the_way_minifb_reads_it() {
+            let screen = shared(1, 1);
+            set_color(screen.clone(), [1.0, 1.0, 0.5, 0.0]);
+            let mut s = screen.lock().unwrap();
+            s.draw(&arr2(&[[0.5, 0.5]]));
+            // minifb takes 0x00RRGGBB and ignores the top byte, which holds alpha here
+            assert_eq!(s.buffer()[0] & 0x00FFFFFF, 0x00FF7F00);
+            assert_eq!(s.buffer()[0] >> 24, 0xFF);
+        }
+
+        #[test]
+        fn composites_half_alpha_over() {
+            let screen = shared(1, 1);
+            set_bg(screen.clone(), [1.0, 0.0, 0.0, 1.0]);
+            set_color(screen.clone(), [0.5, 1.0, 0.0, 0.0]);
+            let mut s = screen.lock().unwrap();
+            s.draw(&arr2(&[[0.5, 0.5]]));
+            // half red over opaque blue: red 0.5, blue 1.0 * (1 - 0.5), still opaque
+            assert_eq!(s.buffer()[0], 0xFF7F007F);
+        }
+
+        #[test]
+        fn saves_png_as_straight_rgba() {
+            let screen = shared(2, 1);
+            set_color(screen.clone(), [0.5, 1.0, 0.0, 0.0]);
+            screen.lock().unwrap().draw(&arr2(&[[0.0, 0.5]]));
+
+            let path = std::env::temp_dir().join(format!("spline-screen-{}.png", std::process::id()));
+            let path = path.to_str().unwrap();
+            save_png(screen, path).unwrap();
+
+            let decoder = png::Decoder::new(BufReader::new(File::open(path).unwrap()));
+            let mut reader = decoder.read_info().unwrap();
+            let mut data = vec![0; reader.output_buffer_size().unwrap()];
+            let info = reader.next_frame(&mut data).unwrap();
+            std::fs::remove_file(path).unwrap();
+
+            assert_eq!(info.color_type, png::ColorType::Rgba);
+            assert_eq!((info.width, info.height), (2, 1));
+            // the premultiplied half red pixel comes back as full red at half alpha
+            assert_eq!(&data[..8], &[255, 0, 0, 127, 0, 0, 0, 0]);
+        }
+    }
 }
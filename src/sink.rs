@@ -0,0 +1,216 @@
+pub mod sink {
+    use std::fs::File;
+    use std::io::{self, BufRead, BufReader, BufWriter, Write};
+    use std::sync::mpsc::Sender;
+    use std::sync::{Arc, Mutex};
+    use ndarray::Array2;
+
+    use crate::screen::screen::{Argb, Gradient, Screen, send_buf, set_color, set_gradient};
+
+    /// Where generators put their samples: one `[x, y, ..]` row per sample, one batch per curve
+    /// (see `Screen::draw` for the columns).
+    pub trait SampleSink {
+        fn send(&mut self, samples: Array2<f64>);
+
+        /// Generators stop early once the sink no longer wants samples, e.g. a cancelled job.
+        fn is_closed(&self) -> bool {
+            false
+        }
+
+        /// Color of the batches that follow, called once per scene layer; ignored by default.
+        fn set_style(&mut self, _color: Argb, _gradient: Option<&Gradient>) {}
+    }
+
+    impl SampleSink for Screen {
+        fn send(&mut self, samples: Array2<f64>) {
+            self.draw(&samples);
+        }
+    }
+
+    impl SampleSink for Arc<Mutex<Screen>> {
+        fn send(&mut self, samples: Array2<f64>) {
+            send_buf(self.clone(), samples);
+        }
+
+        fn set_style(&mut self, color: Argb, gradient: Option<&Gradient>) {
+            set_color(self.clone(), color);
+            set_gradient(self.clone(), gradient.cloned());
+        }
+    }
+
+    /// Sends every batch down a channel; closed once the receiving end is gone.
+    pub struct Channel {
+        tx: Sender<Array2<f64>>,
+        closed: bool,
+    }
+
+    impl Channel {
+        pub fn new(tx: Sender<Array2<f64>>) -> Self {
+            Channel { tx, closed: false }
+        }
+    }
+
+    impl SampleSink for Channel {
+        fn send(&mut self, samples: Array2<f64>) {
+            if !self.closed && self.tx.send(samples).is_err() {
+                self.closed = true;
+            }
+        }
+
+        fn is_closed(&self) -> bool {
+            self.closed
+        }
+    }
+
+    /// Keeps every batch in memory.
+    #[derive(Default)]
+    pub struct Collector {
+        pub batches: Vec<Array2<f64>>,
+    }
+
+    impl SampleSink for Collector {
+        fn send(&mut self, samples: Array2<f64>) {
+            self.batches.push(samples);
+        }
+    }
+
+    /// Writes samples as text, one row per line with space separated columns and an empty line
+    /// after each batch, so a run can be replayed into another sink later.
+    pub struct Recorder<W: Write> {
+        out: W,
+        error: Option<io::Error>,
+    }
+
+    impl Recorder<BufWriter<File>> {
+        pub fn create(path: &str) -> io::Result<Self> {
+            Ok(Recorder::new(BufWriter::new(File::create(path)?)))
+        }
+    }
+
+    impl<W: Write> Recorder<W> {
+        pub fn new(out: W) -> Self {
+            Recorder { out, error: None }
+        }
+
+        /// Flushes and reports the first write error, if any.
+        pub fn finish(mut self) -> io::Result<W> {
+            if let Some(e) = self.error.take() {
+                return Err(e);
+            }
+            self.out.flush()?;
+            Ok(self.out)
+        }
+
+        fn write(&mut self, samples: &Array2<f64>) -> io::Result<()> {
+            for row in samples.rows() {
+                let line: Vec<String> = row.iter().map(|v| v.to_string()).collect();
+                writeln!(self.out, "{}", line.join(" "))?;
+            }
+            writeln!(self.out)
+        }
+    }
+
+    impl<W: Write> SampleSink for Recorder<W> {
+        fn send(&mut self, samples: Array2<f64>) {
+            if self.error.is_none() {
+                if let Err(e) = self.write(&samples) {
+                    self.error = Some(e);
+                }
+            }
+        }
+    }
+
+    /// Sends every batch of a `Recorder` file to `sink`, in order.
+    pub fn replay<S: SampleSink + ?Sized>(path: &str, sink: &mut S) -> io::Result<()> {
+        let mut rows: Vec<Vec<f64>> = Vec::new();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                flush_batch(&mut rows, sink)?;
+                continue;
+            }
+            let row = line.split_whitespace()
+                .map(|v| v.parse::<f64>().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)))
+                .collect::<io::Result<Vec<f64>>>()?;
+            rows.push(row);
+        }
+        flush_batch(&mut rows, sink)
+    }
+
+    fn flush_batch<S: SampleSink + ?Sized>(rows: &mut Vec<Vec<f64>>, sink: &mut S) -> io::Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let ncols = rows[0].len();
+        if rows.iter().any(|r| r.len() != ncols) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "rows of a batch differ in length"));
+        }
+        let flat: Vec<f64> = rows.drain(..).flatten().collect();
+        let samples = Array2::from_shape_vec((flat.len() / ncols, ncols), flat).unwrap();
+        sink.send(samples);
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::sync::mpsc;
+        use crate::spl::spl::{fill_circle_splines, seeded, CircleSplineOpts};
+
+        const ITERATIONS: usize = 6;
+
+        fn collect(seed: u64) -> Collector {
+            let opts = CircleSplineOpts { inum: 20, pnum_from: 10, pnum_to: 11, iterations: ITERATIONS, ..Default::default() };
+            let mut sink = Collector::default();
+            fill_circle_splines(&mut sink, opts, None, &mut seeded(seed));
+            sink
+        }
+
+        #[test]
+        fn collects_one_batch_per_iteration() {
+            let sink = collect(7);
+            assert_eq!(sink.batches.len(), ITERATIONS);
+            for (i, batch) in sink.batches.iter().enumerate() {
+                // [x, y, position, iteration, noise]
+                assert_eq!(batch.ncols(), 5);
+                assert!(batch.nrows() > 1);
+                assert_eq!(batch[[0, 2]], 0.0);
+                assert_eq!(batch[[batch.nrows() - 1, 2]], 1.0);
+                assert!(batch.column(3).iter().all(|&t| t == (i + 1) as f64 / ITERATIONS as f64));
+                assert!(batch.column(4).iter().all(|&n| (0.0..=1.0).contains(&n)));
+            }
+            assert_eq!(sink.batches, collect(7).batches);
+        }
+
+        #[test]
+        fn replays_what_was_recorded() {
+            let recorded = collect(11);
+            let path = std::env::temp_dir().join(format!("spline-sink-{}.txt", std::process::id()));
+            let path = path.to_str().unwrap();
+
+            let mut recorder = Recorder::create(path).unwrap();
+            for batch in recorded.batches.iter() {
+                recorder.send(batch.clone());
+            }
+            recorder.finish().unwrap();
+
+            let mut replayed = Collector::default();
+            replay(path, &mut replayed).unwrap();
+            std::fs::remove_file(path).unwrap();
+            assert_eq!(replayed.batches, recorded.batches);
+        }
+
+        #[test]
+        fn channel_closes_when_the_receiver_is_dropped() {
+            let (tx, rx) = mpsc::channel();
+            let mut sink = Channel::new(tx);
+            sink.send(Array2::zeros((1, 2)));
+            assert!(!sink.is_closed());
+            assert_eq!(rx.recv().unwrap().dim(), (1, 2));
+
+            drop(rx);
+            sink.send(Array2::zeros((1, 2)));
+            assert!(sink.is_closed());
+        }
+    }
+}
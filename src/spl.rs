@@ -1,204 +1,446 @@
 pub mod spl {
-    use std::{env, thread, time};
+    use std::{thread, time};
     use ndarray::Array1;
-    use rand::Rng;
-    use std::ops::{Add, Mul};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use std::ops::Add;
     use std::f64::consts::PI;
     use ndarray::{Array, prelude::*};
     use ndarray_rand::RandomExt;
     use ndarray_rand::rand_distr::Uniform;
-    use std::sync::mpsc::{Sender};
-    use std::sync::{Arc, Mutex};
+    use serde::{Deserialize, Serialize};
 
-    use crate::screen::screen::{Screen, send_buf};
+    use crate::curve::curve::{self, Curve};
+    use crate::interp::interp::{resample, Cardinal, InterpKind, Interpolation, Interpolator, NaturalX};
+    use crate::noise::noise::{NoiseKind, NoiseModel};
+    use crate::screen::screen::arc_length;
+    use crate::sink::sink::SampleSink;
 
     const TWOPI: f64 = 2.0 * PI;
+
+    // every generator draws from an StdRng handed in by the caller, so a seed reproduces the image
+    pub fn seeded(seed: u64) -> StdRng {
+        StdRng::seed_from_u64(seed)
+    }
+
+    // independent child rng, e.g. one per worker thread or per Spline
+    pub fn fork(rng: &mut StdRng) -> StdRng {
+        StdRng::seed_from_u64(rng.gen())
+    }
     
-    #[derive(Clone)]
+    #[derive(Clone, Serialize, Deserialize)]
     pub struct CircleSplineOpts {
         pub stp: f64,
         pub inum: u32,
         pub pnum_from: usize,
         pub pnum_to: usize,
         pub iterations: usize,
+        /// How control points move at each step, the original random walk by default
+        #[serde(default)]
+        pub noise: NoiseKind,
+        /// Interpolate the control points as a closed loop, without a seam where the ends meet
+        #[serde(default)]
+        pub closed: bool,
+        /// Scatter sand grains along the curve instead of drawing its points
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub grain: Option<GrainOpts>,
     }
 
-    pub fn from_env <'a> (s: & 'a mut CircleSplineOpts) {
-        // let mut s = CircleSplineOpts{
-        //     stp: default.stp,
-        //     inum: default.inum,
-        //     pnum_from: default.pnum_from,
-        //     pnum_to: default.pnum_to,
-        //     iterations: default.iterations,
-        // };
-
-        match env::var("STP") {
-            Ok(v) => {
-                s.stp = v.parse::< f64 >().unwrap();
-            },
-            Err(_) => {}
+    // the complex mode's settings, which the other modes start from
+    impl Default for CircleSplineOpts {
+        fn default() -> Self {
+            CircleSplineOpts {
+                stp: 0.000002,
+                inum: 200,
+                pnum_from: 100,
+                pnum_to: 151,
+                iterations: 80,
+                noise: NoiseKind::default(),
+                closed: false,
+                grain: None,
+            }
         }
+    }
 
-        match env::var("INUM") {
-            Ok(v) => {
-                s.inum = v.parse::< u32 >().unwrap();
-            },
-            Err(_) => {}
-        }
+    /// Sand grains scattered over each segment between consecutive interpolated points, as in
+    /// inconvergent's sand-spline; works best with a coarse interpolation (low `inum`).
+    #[derive(Clone, Serialize, Deserialize)]
+    pub struct GrainOpts {
+        /// Grains per segment
+        #[serde(default = "default_grains")]
+        pub per_segment: usize,
+        #[serde(default)]
+        pub distribution: GrainDistribution,
+        /// Random offset of each grain up to this radius, in drawing units (1.0 is the canvas side)
+        #[serde(default)]
+        pub jitter: f64,
+    }
 
-        match env::var("PNUM_FROM") {
-            Ok(v) => {
-                s.pnum_from = v.parse::< usize >().unwrap();
-            },
-            Err(_) => {}
-        }
+    fn default_grains() -> usize {
+        10
+    }
 
-        match env::var("PNUM_TO") {
-            Ok(v) => {
-                s.pnum_to = v.parse::< usize >().unwrap();
-            },
-            Err(_) => {}
+    impl Default for GrainOpts {
+        fn default() -> Self {
+            GrainOpts { per_segment: default_grains(), distribution: GrainDistribution::default(), jitter: 0.0 }
         }
+    }
 
-        match env::var("ITERATIONS") {
-            Ok(v) => {
-                s.iterations = v.parse::< usize >().unwrap();
-            },
-            Err(_) => {}
+    /// Where the grains of a segment fall.
+    #[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum GrainDistribution {
+        /// Anywhere on the segment
+        #[default]
+        Uniform,
+        /// One anywhere within each equal slice of the segment, so they clump less
+        Stratified,
+        /// At the centers of equal slices, no randomness
+        Even,
+    }
+
+    // `per_segment` rows between each pair of consecutive rows, every column interpolated linearly
+    fn grains(samples: &Array2<f64>, opts: &GrainOpts, rng: &mut StdRng) -> Array2<f64> {
+        let n = opts.per_segment;
+        let segments = samples.nrows().saturating_sub(1);
+        let mut out = Array2::<f64>::zeros((segments * n, samples.ncols()));
+        for s in 0..segments {
+            let (a, b) = (samples.row(s), samples.row(s + 1));
+            for k in 0..n {
+                let t = match opts.distribution {
+                    GrainDistribution::Uniform => rng.gen::<f64>(),
+                    GrainDistribution::Stratified => (k as f64 + rng.gen::<f64>()) / n as f64,
+                    GrainDistribution::Even => (k as f64 + 0.5) / n as f64,
+                };
+                let mut row = out.row_mut(s * n + k);
+                for c in 0..a.len() {
+                    row[c] = a[c] + (b[c] - a[c]) * t;
+                }
+                if opts.jitter > 0.0 {
+                    // uniform over the disc
+                    let r = opts.jitter * rng.gen::<f64>().sqrt();
+                    let phi = rng.gen_range(0.0..TWOPI);
+                    row[0] += r * phi.cos();
+                    row[1] += r * phi.sin();
+                }
+            }
         }
+        out
     }
-    struct Spline {
+
+    /// Sand-spline evolution of a closed control polygon: every step displaces the control points
+    /// with a noise model and yields the re-interpolated curve as `[x, y, position, iteration,
+    /// noise]` rows (see `Screen::draw`), or grains along it. Made with `Spline::builder`.
+    ///
+    /// ```ignore
+    /// let path = ndarray::arr2(&[[-0.2, -0.2], [0.2, -0.2], [0.2, 0.2], [-0.2, 0.2]]);
+    /// let spline = Spline::builder(path).segments(50).stp(0.00001).noise(NoiseKind::Curl(2.0))
+    ///     .iterations(60).rng(seeded(7)).build();
+    /// for frame in spline {
+    ///     sink.send(frame);
+    /// }
+    /// ```
+    pub struct Spline {
         g: f64,
         path: ndarray::Array2<f64>,
         num_segments: u32,
-        scale: ndarray::Array1<f64>,
         pnum: usize,
         interpolated_path: ndarray::Array2<f64>,
-        noise: ndarray::Array1<f64>,
+        closed: bool,
+        interpolator: Box<dyn Interpolator>,
+        noise: Box<dyn NoiseModel>,
         i: i64,
+        // number of frames to yield, also normalizes the iteration column
+        iterations: usize,
+        grain: Option<GrainOpts>,
+        spacing: Option<f64>,
+        rng: StdRng,
+    }
+
+    pub struct SplineBuilder {
+        path: Array2<f64>,
+        segments: u32,
+        scale: Option<Array1<f64>>,
+        stp: f64,
+        g: f64,
+        closed: bool,
+        interpolator: Box<dyn Interpolator>,
+        noise: NoiseKind,
+        model: Option<Box<dyn NoiseModel>>,
+        iterations: usize,
+        grain: Option<GrainOpts>,
+        spacing: Option<f64>,
+        rng: Option<StdRng>,
     }
 
-    fn new(path: ndarray::Array2<f64>, num_segments: u32, scale: ndarray::Array1<f64>) -> Spline {
-        let pnum = path.column(0).len();
-        let interpolated_path = rnd_interpolate(& mut path.clone(), num_segments);
-        Spline {
-            g: 0.5,
-            path: path,
-            num_segments: num_segments,
-            scale: scale,
-            pnum: pnum,
-            interpolated_path: interpolated_path,
-            noise: Array1::<f64>::zeros(pnum),
-            i: 0,
+    impl Spline {
+        /// `path` holds the control points as `[x, y]` rows centered on 0, at least three of them.
+        pub fn builder(path: Array2<f64>) -> SplineBuilder {
+            SplineBuilder {
+                path,
+                segments: 200,
+                scale: None,
+                stp: 0.000002,
+                g: 0.5,
+                closed: false,
+                interpolator: Box::new(Cardinal { tension: 0.5 }),
+                noise: NoiseKind::default(),
+                model: None,
+                iterations: 100,
+                grain: None,
+                spacing: None,
+                rng: None,
+            }
+        }
+    }
+
+    impl SplineBuilder {
+        /// Interpolated segments between consecutive control points [default: 200]
+        pub fn segments(mut self, segments: u32) -> Self {
+            self.segments = segments;
+            self
+        }
+
+        /// Noise step of every control point, one per row of the path
+        pub fn scale(mut self, scale: Array1<f64>) -> Self {
+            self.scale = Some(scale);
+            self
+        }
+
+        /// The usual scale profile, a ramp of `stp` per control point centered on 0 [default: 0.000002];
+        /// ignored when `scale` is given
+        pub fn stp(mut self, stp: f64) -> Self {
+            self.stp = stp;
+            self
+        }
+
+        /// Offset added to both coordinates of every sample, 0.5 centers the path on the canvas [default: 0.5]
+        pub fn center(mut self, g: f64) -> Self {
+            self.g = g;
+            self
+        }
+
+        /// Treat the path as a closed loop whose last point connects back to the first; the path
+        /// should not repeat its first point at the end [default: false]
+        pub fn closed(mut self, closed: bool) -> Self {
+            self.closed = closed;
+            self
+        }
+
+        /// How the control points become a curve [default: cardinal with tension 0.5]
+        pub fn interpolator(mut self, interpolator: Box<dyn Interpolator>) -> Self {
+            self.interpolator = interpolator;
+            self
+        }
+
+        pub fn noise(mut self, noise: NoiseKind) -> Self {
+            self.noise = noise;
+            self
+        }
+
+        /// A custom noise model, used instead of `noise`
+        pub fn noise_model(mut self, model: Box<dyn NoiseModel>) -> Self {
+            self.model = Some(model);
+            self
+        }
+
+        /// Frames to yield [default: 100]
+        pub fn iterations(mut self, iterations: usize) -> Self {
+            self.iterations = iterations;
+            self
+        }
+
+        pub fn grain(mut self, grain: Option<GrainOpts>) -> Self {
+            self.grain = grain;
+            self
+        }
+
+        /// Resample every frame by arc length, at most `spacing` apart in path units, before any
+        /// grains are scattered; `segments` then only sets how finely the curve is traced
+        pub fn spacing(mut self, spacing: Option<f64>) -> Self {
+            self.spacing = spacing;
+            self
+        }
+
+        /// Source of all randomness [default: `seeded(0)`]
+        pub fn rng(mut self, rng: StdRng) -> Self {
+            self.rng = Some(rng);
+            self
+        }
+
+        /// # Panics
+        /// When the path has fewer than three points or is not two columns wide, or a `scale`
+        /// does not have one step per control point.
+        pub fn build(self) -> Spline {
+            let pnum = self.path.nrows();
+            assert!(pnum >= 3 && self.path.ncols() == 2, "a spline path needs at least 3 [x, y] rows, got {:?}", self.path.dim());
+            let stp = self.stp;
+            let scale = self.scale.unwrap_or_else(|| noise_scale(pnum, stp));
+            assert_eq!(scale.len(), pnum, "scale needs one step per control point");
+
+            let mut rng = self.rng.unwrap_or_else(|| seeded(0));
+            let interpolated_path = self.interpolator.interpolate(&self.path, self.segments, self.closed);
+            let noise = match self.model {
+                Some(model) => model,
+                None => self.noise.model(&self.path, scale, &mut rng),
+            };
+            Spline {
+                g: self.g,
+                path: self.path,
+                num_segments: self.segments,
+                pnum,
+                interpolated_path,
+                closed: self.closed,
+                interpolator: self.interpolator,
+                noise,
+                i: 0,
+                iterations: self.iterations,
+                grain: self.grain,
+                spacing: self.spacing,
+                rng,
+            }
+        }
+    }
+
+    impl Iterator for Spline {
+        type Item = Array2<f64>;
+
+        fn next(&mut self) -> Option<Array2<f64>> {
+            if self.i as usize >= self.iterations {
+                return None;
+            }
+            Some(self.frame())
         }
     }
 
     impl Spline {
-        fn next(&mut self) -> Array2<f64> {
-            let rand = Array::random(self.pnum, Uniform::new(-2.0, 0.0));
-            let r = rand.map(|n| 1.0-n);
-            self.noise = self.noise.clone().add(r.mul(self.scale.clone()));
-            let a = Array::random(self.pnum, Uniform::new(0.0, TWOPI));
+        /// The current control points, `[x, y]` rows centered on 0.
+        pub fn path(&self) -> &Array2<f64> {
+            &self.path
+        }
 
-            let rnd_x = a.map(|n| n.cos()).mul(self.noise.clone());
-            let rnd_y = a.map(|n| n.sin()).mul(self.noise.clone());
-            let p = ndarray::stack(ndarray::Axis(1), &[rnd_x.view(), rnd_y.view()]).unwrap();
+        // rows are [x, y, position, iteration, noise], see `send_buf`; grains when enabled
+        fn frame(&mut self) -> Array2<f64> {
+            let p = self.noise.step(&self.path, self.i as usize, &mut self.rng);
 
             self.path = self.path.clone().add(p);
-            self.interpolated_path = rnd_interpolate(& mut self.path.clone(), self.num_segments);
+            self.interpolated_path = self.interpolator.interpolate(&self.path, self.num_segments, self.closed);
 
             self.i += 1;
-            self.interpolated_path.map(|n| *n + self.g)
+            let xy = self.interpolated_path.map(|n| *n + self.g);
+
+            let rows = xy.nrows();
+            let position = arr1(&arc_length(&xy));
+            let iteration = Array1::from_elem(rows, self.i as f64 / self.iterations.max(1) as f64);
+            let amplitude = self.noise.amplitude();
+            let max_noise = amplitude.fold(0.0f64, |m, n| m.max(n.abs()));
+            let noise = Array1::from_shape_fn(rows, |r| {
+                let p = (r * (self.pnum - 1) + (rows - 1) / 2) / (rows - 1).max(1);
+                if max_noise > 0.0 { amplitude[p].abs() / max_noise } else { 0.0 }
+            });
+            let mut samples = ndarray::stack(ndarray::Axis(1), &[xy.column(0), xy.column(1), position.view(), iteration.view(), noise.view()]).unwrap();
+            if let Some(spacing) = self.spacing {
+                samples = resample(&samples, spacing);
+            }
+            match &self.grain {
+                Some(g) => grains(&samples, g, &mut self.rng),
+                None => samples,
+            }
         }
     }
 
-    // cubic_spline v1.0.0 (working fine)
-    fn rnd_interpolate(xy: &mut ndarray::Array2<f64>, num_segments: u32) -> Array2<f64> {
-        let mut vec: Vec<Point> = Vec::<Point>::with_capacity(xy.column(0).len());
-        for r in xy.rows() {
-            vec.push(Point::new(r[0], r[1]));
-        }
-        let opts = SplineOpts::new().tension(0.5).num_of_segments(num_segments);
-        let points = Points::try_from(&vec).unwrap();
-        let result = points.calc_spline(&opts).unwrap();
-
-        let result_vec = result.get_ref();
-        let ax: Vec<f64> = result_vec.into_iter().map(|p| p.x).collect();
-        let ay: Vec<f64> = result_vec.into_iter().map(|p| p.y).collect();
-        ndarray::stack(ndarray::Axis(1), &[arr1(&ax).view(), arr1(&ay).view()]).unwrap()
+    // per control point noise step, from -pnum*stp/2 up; Array::range rounding could yield pnum+1 values
+    fn noise_scale(pnum: usize, stp: f64) -> Array1<f64> {
+        let start = -(pnum as f64) * stp / 2.0;
+        Array1::from_shape_fn(pnum, |i| start + stp * i as f64)
     }
 
-    pub fn fill_circle(tx: Sender<ndarray::Array2<f64>>) {
-        thread::spawn(move || {
-            let mut rng = rand::thread_rng();
-            let pnum: usize = 10000;
-            let shift = rng.gen_range(0.0..TWOPI);
-            let a2 = Array1::linspace(0.0, TWOPI, pnum);
-            let a = a2.map(|n| n + shift);
-    
-            let path_stack = ndarray::stack(ndarray::Axis(1), &[a.map(|n| n.cos()).view(), a.map(|n| n.sin()).view()]).unwrap();
-            let scale_path = rng.gen_range(0.1..0.5);
-            let path = path_stack.map(|n| n*scale_path+0.5);
+    // generators write every curve to a SampleSink: the canvas, a channel, a file or memory
 
-            for _ in 0..10000 {
-                tx.send(path.clone()).unwrap();
+    pub fn fill_circle<S: SampleSink + ?Sized>(sink: &mut S, rng: &mut StdRng) {
+        let pnum: usize = 10000;
+        let shift = rng.gen_range(0.0..TWOPI);
+        let a2 = Array1::linspace(0.0, TWOPI, pnum);
+        let a = a2.map(|n| n + shift);
+
+        let path_stack = ndarray::stack(ndarray::Axis(1), &[a.map(|n| n.cos()).view(), a.map(|n| n.sin()).view()]).unwrap();
+        let scale_path = rng.gen_range(0.1..0.5);
+        let path = path_stack.map(|n| n*scale_path+0.5);
+
+        for _ in 0..10000 {
+            if sink.is_closed() {
+                return;
             }
-        });
+            sink.send(path.clone());
+        }
     }
 
-    pub fn fill_circle_splines (screen: Arc<Mutex<Screen>>, opts: CircleSplineOpts) {
-        let mut rng = rand::thread_rng();
-    
+    pub fn fill_circle_splines<S: SampleSink + ?Sized>(sink: &mut S, opts: CircleSplineOpts, interp: Option<&Interpolation>, rng: &mut StdRng) {
+        let interp = interp.cloned().unwrap_or_default();
         let scale_path= rng.gen_range(0.1..0.4);
         let pnum: usize = rng.gen_range(opts.pnum_from..opts.pnum_to);
         let shift = rng.gen_range(0.0..TWOPI);
 
-        let a2 = Array1::linspace(0.0, TWOPI, pnum);
+        // an open path repeats its first point at the end, a closed one wraps around to it instead
+        let a2 = if opts.closed {
+            Array1::from_shape_fn(pnum, |i| TWOPI * i as f64 / pnum as f64)
+        } else {
+            Array1::linspace(0.0, TWOPI, pnum)
+        };
         let a = a2.map(|n| n + shift);
 
         let path_stack = ndarray::stack(ndarray::Axis(1), &[a.map(|n| n.cos()).view(), a.map(|n| n.sin()).view()]).unwrap();
         let path = path_stack.map(|n| n * scale_path);
 
         //let scale = Array::range(0., (pnum as f64)*STP, STP);
-        let scale = Array::range(-1.0 * (pnum as f64) * opts.stp / 2.0, (pnum as f64)*opts.stp / 2.0, opts.stp );
-        let mut s = new(path, opts.inum, scale);
-        for _ in 0..opts.iterations {
-            send_buf(screen.clone(), s.next());
+        let scale = noise_scale(pnum, opts.stp);
+        let spline = Spline::builder(path).segments(interp.segments_or(opts.inum)).scale(scale).noise(opts.noise)
+            .interpolator(interp.interpolator()).closed(opts.closed).iterations(opts.iterations).grain(opts.grain.clone()).spacing(interp.step()).rng(fork(rng)).build();
+        for frame in spline {
+            if sink.is_closed() {
+                return;
+            }
+            sink.send(frame);
         }
     }
 
 
 
-    pub fn fill_rand_splines(screen: Arc<Mutex<Screen>>) {
-        extern crate peroxide;
-        use peroxide::prelude::{CubicSpline};
+    // a natural cubic y(x) through four random nodes, or a curve through them by `interp`
+    pub fn fill_rand_splines<S: SampleSink + ?Sized>(sink: &mut S, interp: Option<&Interpolation>, rng: &mut StdRng) {
+        let interp = interp.cloned().unwrap_or_default();
+        let interpolator: Box<dyn Interpolator> = match interp.kind {
+            None | Some(InterpKind::NaturalX) => Box::new(NaturalX { domain: Some((0.01, 0.99)) }),
+            Some(_) => interp.interpolator(),
+        };
 
-        let mut rng = rand::thread_rng();
         for _ in 0..700 {
-            let r1 = Array::random((1, 4), Uniform::new(0., 1.));
-            let r2 = Array::random((1, 4), Uniform::new(0., 1.));
+            if sink.is_closed() {
+                return;
+            }
+            let r1 = Array::random_using((1, 4), Uniform::new(0., 1.), rng);
+            let r2 = Array::random_using((1, 4), Uniform::new(0., 1.), rng);
             
             let x = r1.into_raw_vec();
             let y = r2.into_raw_vec();
 
-            let spl = CubicSpline::from_nodes(x, y);
-
-            let a = Array1::linspace(0.01, 0.99, rng.gen_range(200..1800));
-            let b = a.map(|n| spl.eval(*n));
-            let d = ndarray::stack(ndarray::Axis(1), &[a.view(), b.view()]).unwrap();
-
-            send_buf(screen.clone(), d);
+            let count = rng.gen_range(200..1800);
+            let mut nodes: Vec<(f64, f64)> = x.into_iter().zip(y).collect();
+            nodes.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let xy = Array2::from_shape_fn((nodes.len(), 2), |(r, c)| if c == 0 { nodes[r].0 } else { nodes[r].1 });
+            sink.send(interp.resample(interpolator.interpolate(&xy, interp.segments_or(count as u32 / 3), false)));
         }
     }
 
-    pub fn fill_rand(tx: Sender<ndarray::Array2<f64>>) {
-        thread::spawn(move || {
-            for _ in 0..1000 {
-                let a = Array::random((5, 2), Uniform::new(0., 1.));
-                tx.send(a).unwrap();
-                thread::sleep(time::Duration::from_millis(200));   
+    pub fn fill_rand<S: SampleSink + ?Sized>(sink: &mut S, rng: &mut StdRng) {
+        for _ in 0..1000 {
+            if sink.is_closed() {
+                return;
             }
-        });
+            let a = Array::random_using((5, 2), Uniform::new(0., 1.), rng);
+            sink.send(a);
+            thread::sleep(time::Duration::from_millis(200));   
+        }
     }
 
 
@@ -209,24 +451,38 @@ pub mod spl {
     /// 
     /// 
     use num::Complex;
-    use cubic_spline::{Points, Point, SplineOpts, TryFrom};
 
     fn circle(x: f64, y: f64, segments: usize, scale: f64) -> ndarray::Array2<f64> {
         let a = Array1::linspace(0.0, TWOPI, segments);
-        ndarray::stack(ndarray::Axis(1), &[a.map(|n| n.cos() * scale/2.0 + y).view(), a.map(|n| n.sin() * scale/2.0 + x).view()])
+        ndarray::stack(ndarray::Axis(1), &[a.map(|n| n.cos() * scale/2.0 + x).view(), a.map(|n| n.sin() * scale/2.0 + y).view()])
             .unwrap()
     }
 
+    /// Sum of `exp(a + i*(b*theta + phase))*c` terms for `[a, b, c]` params over theta in 0..2π;
+    /// `phases` is empty or holds one phase per term.
+    #[derive(Clone, Debug)]
     pub struct ComplexEquationParams {
-        pub params: Vec<[f64; 3]>
+        pub params: Vec<[f64; 3]>,
+        pub phases: Vec<f64>,
     }
 
     impl ComplexEquationParams {
+        pub fn new(params: Vec<[f64; 3]>) -> Self {
+            ComplexEquationParams { params, phases: Vec::new() }
+        }
+
+        fn term(&self, i: usize, theta: f64) -> Complex<f64> {
+            let row = self.params[i];
+            match self.phases.get(i) {
+                Some(phase) => Complex::new(row[0], row[1] * theta + phase).exp() * row[2],
+                None => Complex::new(row[0], row[1] * theta).exp() * row[2],
+            }
+        }
+
         fn f(&self, theta: f64) -> Complex<f64> {
-            let row = self.params[0];
-            let mut c = Complex::new(row[0], row[1] * theta).exp() * row[2];
-            for row in self.params[1..].iter() {
-                c = c + Complex::new(row[0], row[1] * theta).exp() * row[2];
+            let mut c = self.term(0, theta);
+            for i in 1..self.params.len() {
+                c += self.term(i, theta);
             }
             return c;
         }
@@ -240,6 +496,13 @@ pub mod spl {
         }
     }
 
+    impl Curve for ComplexEquationParams {
+        fn at(&self, t: f64) -> [f64; 2] {
+            let c = self.f(TWOPI * t);
+            [c.re, c.im]
+        }
+    }
+
     fn complex_circle(pnum: usize, f: fn(f64) -> Complex<f64>, shift_x: f64, shift_y: f64 ) -> ndarray::Array2<f64> {
         let l = Array1::linspace(0.0, TWOPI, pnum);
         let c = l.map(|theta| f(*theta));
@@ -248,67 +511,82 @@ pub mod spl {
         ndarray::stack(ndarray::Axis(1), &[x.view(), y.view()]).unwrap()
     }
 
-    pub fn fill_complex_circle(screen: Arc<Mutex<Screen>>, params: Vec<[f64; 3]>) {
-        let cc = ComplexEquationParams{params: params};
-        send_buf(screen.clone(), cc.complex_circle(10000, 0.5, 0.5));
+    pub fn fill_complex_circle<S: SampleSink + ?Sized>(sink: &mut S, cc: ComplexEquationParams) {
+        sink.send(cc.complex_circle(10000, 0.5, 0.5));
     }
 
-    pub fn random_complex_splines(screen: Arc<Mutex<Screen>>) {
-        let mut rng = rand::thread_rng();
+    pub fn random_complex_splines<S: SampleSink + ?Sized>(sink: &mut S, rng: &mut StdRng) {
         for _ in 0..100 {
-            fill_complex_spline(screen.clone(), CircleSplineOpts{
-                inum: 200,
+            if sink.is_closed() {
+                return;
+            }
+            fill_complex_spline(sink, CircleSplineOpts{
                 stp: 0.000001,
                 pnum_from: 150,
                 pnum_to: 181,
                 iterations: 50,
-            }, vec![
+                ..CircleSplineOpts::default()
+            }, ComplexEquationParams::new(vec![
                     [rng.gen_range(-5.0..5.0), 10.0, 0.01],
                     [3.0, -1.0, 0.01],
                     [0.5, 0.5, 0.01],
-                ]);
+                ]), None, rng);
             
             thread::sleep(time::Duration::from_millis(1000))
         }
     }
 
-    pub fn fill_complex_spline(screen: Arc<Mutex<Screen>>, opts: CircleSplineOpts, params: Vec<[f64; 3]>) {
-        let mut rng = rand::thread_rng();
-    
+    pub fn fill_complex_spline<S: SampleSink + ?Sized>(sink: &mut S, opts: CircleSplineOpts, cc: ComplexEquationParams, interp: Option<&Interpolation>, rng: &mut StdRng) {
+        let interp = interp.cloned().unwrap_or_default();
         let scale_path = 0.8;
         let pnum: usize = rng.gen_range(opts.pnum_from..opts.pnum_to);
        
-        let cc = ComplexEquationParams{params: params};
-        let path_stack = cc.complex_circle(pnum, 0.0, 0.0);
+        let path_stack = if opts.closed {
+            cc.complex_circle(pnum + 1, 0.0, 0.0).slice(s![..pnum, ..]).to_owned()
+        } else {
+            cc.complex_circle(pnum, 0.0, 0.0)
+        };
 
         let path = path_stack.map(|n| n * scale_path);
 
-        let scale = Array::range(-1.0 * (pnum as f64) * opts.stp / 2.0, (pnum as f64)*opts.stp / 2.0, opts.stp );
-        let mut s = new(path, opts.inum, scale);
-        for _ in 0..opts.iterations {
-            send_buf(screen.clone(), s.next());
+        let scale = noise_scale(pnum, opts.stp);
+        let spline = Spline::builder(path).segments(interp.segments_or(opts.inum)).scale(scale).noise(opts.noise)
+            .interpolator(interp.interpolator()).closed(opts.closed).iterations(opts.iterations).grain(opts.grain.clone()).spacing(interp.step()).rng(fork(rng)).build();
+        for frame in spline {
+            if sink.is_closed() {
+                return;
+            }
+            sink.send(frame);
         }
     }
 
-    fn new_spline(x1: f64, x2: f64, y1: f64, y2: f64, points: usize) -> Array2<f64> {
-        let x = Array::random(points, Uniform::new(x1, x2));
-        let y = Array::random(points, Uniform::new(y1, y2));
-
-        let xy = ndarray::stack(ndarray::Axis(1), &[x.view(), y.view()]).unwrap();
-        let mut vec: Vec<Point> = Vec::<Point>::with_capacity(xy.column(0).len());
-        for r in xy.rows() {
-            vec.push(Point::new(r[0], r[1]));
+    /// Sand-spline evolution starting from `pnum_from..pnum_to` points of `curve`, scaled to
+    /// `size` across (1 being the canvas side).
+    pub fn fill_curve_spline<S: SampleSink + ?Sized>(sink: &mut S, opts: CircleSplineOpts, curve: &dyn Curve, size: f64, interp: Option<&Interpolation>, rng: &mut StdRng) {
+        let interp = interp.cloned().unwrap_or_default();
+        let pnum: usize = rng.gen_range(opts.pnum_from..opts.pnum_to);
+        let path = curve::fit(curve.path(pnum, opts.closed), size / 2.0);
+
+        let scale = noise_scale(pnum, opts.stp);
+        let spline = Spline::builder(path).segments(interp.segments_or(opts.inum)).scale(scale).noise(opts.noise)
+            .interpolator(interp.interpolator()).closed(opts.closed).iterations(opts.iterations).grain(opts.grain.clone()).spacing(interp.step()).rng(fork(rng)).build();
+        for frame in spline {
+            if sink.is_closed() {
+                return;
+            }
+            sink.send(frame);
         }
-        let opts = SplineOpts::new().tension(0.5).num_of_segments(128);
-        let points = Points::try_from(&vec).unwrap();
-        let result = points.calc_spline(&opts).unwrap();
+    }
 
-        let result_vec = result.get_ref();
-        let ax: Vec<f64> = result_vec.into_iter().map(|p| p.x).collect();
-        let ay: Vec<f64> = result_vec.into_iter().map(|p| p.y).collect();
-        ndarray::stack(ndarray::Axis(1), &[arr1(&ax).view(), arr1(&ay).view()]).unwrap()
+    fn new_spline(x1: f64, x2: f64, y1: f64, y2: f64, points: usize, interp: &Interpolation, rng: &mut StdRng) -> Array2<f64> {
+        let x = Array::random_using(points, Uniform::new(x1, x2), rng);
+        let y = Array::random_using(points, Uniform::new(y1, y2), rng);
+
+        let xy = ndarray::stack(ndarray::Axis(1), &[x.view(), y.view()]).unwrap();
+        interp.resample(interp.interpolator().interpolate(&xy, interp.segments_or(128), false))
     }
 
+    #[derive(Clone, Serialize, Deserialize)]
     pub struct HieroglyphOpts {
         pub row_count: usize,// = 12;
         pub col_count: usize,// = 12;
@@ -318,27 +596,37 @@ pub mod spl {
         pub points: usize,// = 6;
     }
 
-    pub fn fill_spline_hieroglyph(screen: Arc<Mutex<Screen>>, opts: HieroglyphOpts) {
+    pub fn fill_spline_hieroglyph<S: SampleSink + ?Sized>(sink: &mut S, opts: HieroglyphOpts, interp: Option<&Interpolation>, rng: &mut StdRng) {
+        let interp = interp.cloned().unwrap_or_default();
         for i in 0..opts.row_count {
             for j in 0..opts.col_count {
+                if sink.is_closed() {
+                    return;
+                }
                 let ii = i as f64;
                 let jj = j as f64;
 
-                let x1 = opts.padding + ((opts.height + opts.padding) * ii);
-                let x2 = x1 + opts.height;
+                let x1 = opts.padding + ((opts.width + opts.padding) * jj);
+                let x2 = x1 + opts.width;
 
-                let y1 = opts.padding + ((opts.width + opts.padding) * jj);
-                let y2 = y1 + opts.width;
+                let y1 = opts.padding + ((opts.height + opts.padding) * ii);
+                let y2 = y1 + opts.height;
 
-                let d = new_spline(x1, x2, y1, y2, opts.points);
-                send_buf(screen.clone(), d)
+                let d = new_spline(x1, x2, y1, y2, opts.points, &interp, rng);
+                sink.send(d)
             }
         }
     }
 
-    fn new_spline_row(x1: f64, x2: f64, y1: f64, y2: f64, points: usize, iterations: usize) -> Array2<f64> {
-        let mut rng = rand::thread_rng();
+    // row `row` of `col_count` characters, the first one in x1..x2, the rest following it to the right
+    fn new_spline_row(opts: &ScriptOpts, row: usize, interp: &Interpolation, rng: &mut StdRng) -> Array2<f64> {
+        let x1 = opts.padding;
+        let x2 = x1 + opts.char_width;
+
+        let y1 = opts.padding + ((opts.height + opts.padding) * row as f64);
+        let y2 = y1 + opts.height;
 
+        let (points, iterations) = (opts.points, opts.col_count);
         let points_count = rng.gen_range(points-2..points+2);
 
         let mut xx: ndarray::Array1<f64> = arr1(&[]);
@@ -350,44 +638,39 @@ pub mod spl {
                 continue;
             }
 
-            let mut x = Array::random(points_count, Uniform::new(x1, x2));
+            let mut x = Array::random_using(points_count, Uniform::new(x1, x2), rng);
+            x = x.map(|n| n + i as f64 * (x2-x1));
+
+            let y = Array::random_using(points_count, Uniform::new(y1, y2), rng);
 
             // transition (up or down)
             // if i % 2 == 0 {
-            //     x[points-1] = x1;
+            //     y[points-1] = y1;
             // } else {
-            //     x[points-1] = x2;
+            //     y[points-1] = y2;
             // }
 
-            let mut y = Array::random(points_count, Uniform::new(y1, y2));
-            y = y.map(|n| n + i as f64 * (y2-y1));
             xx.append(Axis(0), x.view()).unwrap();
             yy.append(Axis(0), y.view()).unwrap();
         }
         let xy = ndarray::stack(ndarray::Axis(1), &[xx.view(), yy.view()]).unwrap();
-        let mut vec: Vec<Point> = Vec::<Point>::with_capacity(xy.column(0).len());
-        for r in xy.rows() {
-            vec.push(Point::new(r[0], r[1]));
-        }
-        let opts = SplineOpts::new().tension(0.5).num_of_segments(128);
-        let points = Points::try_from(&vec).unwrap();
-        let result = points.calc_spline(&opts).unwrap();
+        let result = interp.interpolator().interpolate(&xy, interp.segments_or(128), false);
 
-        let result_vec = result.get_ref();
-        let mut ax: Vec<f64> = result_vec.into_iter().map(|p| p.x).collect();
-        let mut ay: Vec<f64> = result_vec.into_iter().map(|p| p.y).collect();
+        let mut ax: Vec<f64> = result.column(0).to_vec();
+        let ay: Vec<f64> = result.column(1).to_vec();
         
         // incline
-        let mut incline = y2-y1 / 2.0;
-        let change = incline / ay.len() as f64;
-        for i in 0..ay.len() {
-            ay[i] += ay[i] + incline;
+        let mut incline = x2-x1 / 2.0;
+        let change = incline / ax.len() as f64;
+        for x in ax.iter_mut() {
+            *x += *x + incline;
             incline = incline - change;
         }
 
-        ndarray::stack(ndarray::Axis(1), &[arr1(&ax).view(), arr1(&ay).view()]).unwrap()
+        interp.resample(ndarray::stack(ndarray::Axis(1), &[arr1(&ax).view(), arr1(&ay).view()]).unwrap())
     }
 
+    #[derive(Clone, Serialize, Deserialize)]
     pub struct ScriptOpts {
         pub row_count: usize,// = 12;
         pub col_count: usize,// = 12;
@@ -397,18 +680,14 @@ pub mod spl {
         pub points: usize,// = 6;
     }
 
-    pub fn fill_spline_script(screen: Arc<Mutex<Screen>>, opts: ScriptOpts) {
+    pub fn fill_spline_script<S: SampleSink + ?Sized>(sink: &mut S, opts: ScriptOpts, interp: Option<&Interpolation>, rng: &mut StdRng) {
+        let interp = interp.cloned().unwrap_or_default();
         for i in 0..opts.row_count {
-            let ii = i as f64;
-
-            let x1 = opts.padding + ((opts.height + opts.padding) * ii);
-            let x2 = x1 + opts.height;
-
-            let y1 = opts.padding;
-            let y2 = y1 + opts.char_width;
-
-            let d = new_spline_row(x1, x2, y1, y2, opts.points, opts.col_count);
-            send_buf(screen.clone(), d);
+            if sink.is_closed() {
+                return;
+            }
+            let d = new_spline_row(&opts, i, &interp, rng);
+            sink.send(d);
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,162 @@
+pub mod svg {
+    use std::fs::File;
+    use std::io::{self, BufWriter, Write};
+    use ndarray::Array2;
+
+    use crate::scene::scene::Scene;
+    use crate::screen::screen::{Argb, BlendMode, Gradient, arc_length, to_pixel};
+    use crate::sink::sink::SampleSink;
+
+    // gradients are approximated by splitting a curve into this many single-colored pieces
+    const GRADIENT_STEPS: usize = 32;
+
+    /// Writes every batch as a stroked `<polyline>`, in pixel units of the scene canvas, one `<g>`
+    /// per layer. Stroke opacity is the color's alpha, so overlapping strokes build up like on the
+    /// raster canvas; splat and tone mapping do not apply.
+    pub struct Svg<W: Write> {
+        out: W,
+        width: usize,
+        height: usize,
+        stroke_width: f64,
+        // points closer than this to the previous one (in pixels) are dropped
+        min_step: f64,
+        color: Argb,
+        gradient: Option<Gradient>,
+        in_layer: bool,
+        error: Option<io::Error>,
+    }
+
+    impl Svg<BufWriter<File>> {
+        pub fn create(path: &str, scene: &Scene, stroke_width: f64) -> io::Result<Self> {
+            Svg::new(BufWriter::new(File::create(path)?), scene, stroke_width)
+        }
+    }
+
+    fn rgb(c: &Argb) -> String {
+        let b = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        format!("#{:02x}{:02x}{:02x}", b(c[1]), b(c[2]), b(c[3]))
+    }
+
+    // CSS equivalent, where there is one
+    fn blend_css(blend: BlendMode) -> Option<&'static str> {
+        match blend {
+            BlendMode::Over => None,
+            BlendMode::Additive => Some("plus-lighter"),
+            BlendMode::Screen => Some("screen"),
+            BlendMode::Multiply => Some("multiply"),
+            BlendMode::Max => Some("lighten"),
+            BlendMode::Subtract | BlendMode::Erase => None,
+        }
+    }
+
+    impl<W: Write> Svg<W> {
+        /// Writes the header and the background of `scene`.
+        pub fn new(mut out: W, scene: &Scene, stroke_width: f64) -> io::Result<Self> {
+            writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+                w = scene.width, h = scene.height)?;
+            if let Some(mode) = blend_css(scene.blend) {
+                writeln!(out, "<style>polyline {{ mix-blend-mode: {}; }}</style>", mode)?;
+            }
+            writeln!(out, r#"<rect width="100%" height="100%" fill="{}" fill-opacity="{}"/>"#, rgb(&scene.bg), scene.bg[0])?;
+            Ok(Svg {
+                out,
+                width: scene.width,
+                height: scene.height,
+                stroke_width,
+                min_step: 0.5,
+                color: [1.0, 0.0, 0.0, 0.0],
+                gradient: None,
+                in_layer: false,
+                error: None,
+            })
+        }
+
+        /// Closes the document and reports the first write error, if any.
+        pub fn finish(mut self) -> io::Result<W> {
+            if let Some(e) = self.error.take() {
+                return Err(e);
+            }
+            if self.in_layer {
+                writeln!(self.out, "</g>")?;
+            }
+            writeln!(self.out, "</svg>")?;
+            self.out.flush()?;
+            Ok(self.out)
+        }
+
+        fn polyline(&mut self, points: &[(f64, f64)], color: &Argb) -> io::Result<()> {
+            if points.len() < 2 {
+                return Ok(());
+            }
+            write!(self.out, r#"<polyline fill="none" stroke="{}" stroke-opacity="{}" stroke-width="{}" points=""#,
+                rgb(color), color[0], self.stroke_width)?;
+            for (i, (x, y)) in points.iter().enumerate() {
+                write!(self.out, "{}{:.2},{:.2}", if i == 0 { "" } else { " " }, x, y)?;
+            }
+            writeln!(self.out, r#""/>"#)
+        }
+
+        fn write(&mut self, samples: &Array2<f64>) -> io::Result<()> {
+            if !self.in_layer {
+                writeln!(self.out, "<g>")?;
+                self.in_layer = true;
+            }
+            let ts = self.gradient.as_ref().map(|g| {
+                if samples.ncols() > g.by.column() {
+                    samples.column(g.by.column()).to_vec()
+                } else {
+                    arc_length(samples)
+                }
+            });
+            let step = |t: f64| ((t.clamp(0.0, 1.0) * GRADIENT_STEPS as f64) as usize).min(GRADIENT_STEPS - 1);
+
+            let mut points = Vec::<(f64, f64)>::new();
+            let mut piece = ts.as_ref().map_or(0, |ts| step(ts[0]));
+            let rows = samples.nrows();
+            for (r, row) in samples.rows().into_iter().enumerate() {
+                let p = to_pixel(self.width, self.height, row[0], row[1]);
+                if let Some(ts) = &ts {
+                    let s = step(ts[r]);
+                    if s != piece {
+                        // the next piece starts where this one ends
+                        points.push(p);
+                        let color = self.gradient.as_ref().unwrap().at((piece as f64 + 0.5) / GRADIENT_STEPS as f64);
+                        self.polyline(&points, &color)?;
+                        points.clear();
+                        piece = s;
+                    }
+                }
+                let near = points.last().is_some_and(|l| (p.0 - l.0).hypot(p.1 - l.1) < self.min_step);
+                if !near || r + 1 == rows {
+                    points.push(p);
+                }
+            }
+            let color = match &self.gradient {
+                Some(g) => g.at((piece as f64 + 0.5) / GRADIENT_STEPS as f64),
+                None => self.color,
+            };
+            self.polyline(&points, &color)
+        }
+    }
+
+    impl<W: Write> SampleSink for Svg<W> {
+        fn send(&mut self, samples: Array2<f64>) {
+            if self.error.is_none() {
+                if let Err(e) = self.write(&samples) {
+                    self.error = Some(e);
+                }
+            }
+        }
+
+        fn set_style(&mut self, color: Argb, gradient: Option<&Gradient>) {
+            self.color = color;
+            self.gradient = gradient.cloned();
+            if self.in_layer && self.error.is_none() {
+                if let Err(e) = writeln!(self.out, "</g>") {
+                    self.error = Some(e);
+                }
+            }
+            self.in_layer = false;
+        }
+    }
+}
@@ -0,0 +1,116 @@
+pub mod viewer {
+    extern crate minifb;
+    use std::time::{Duration, Instant};
+    use minifb::{Key, KeyRepeat, Window, WindowOptions};
+
+    use crate::job::job::{self, RenderJob};
+    use crate::scene::scene::Scene;
+
+    const KEYS: &str = "Space pause, S snapshot, R reseed, Esc close, Q quit";
+
+    // how often the window shows the canvas while a job draws
+    const FRAME: Duration = Duration::from_millis(100);
+
+    /// Why `view` returned.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Exit {
+        /// Escape was pressed
+        Closed,
+        /// The auto-close timeout ran out
+        Timeout,
+        /// Q was pressed or the window was closed; callers showing several scenes stop too
+        Quit,
+    }
+
+    pub struct ViewerOpts {
+        /// Close this long after drawing finished (not while paused)
+        pub timeout: Option<Duration>,
+    }
+
+    /// Draws `scene` in a window, refreshing it while the job runs. Snapshots are saved as
+    /// `snapshot-<seed>-<n>.png`; reseeding restarts the scene with a fresh random seed.
+    pub fn view(mut scene: Scene, opts: &ViewerOpts) -> Exit {
+        let mut window = Window::new(KEYS, scene.width, scene.height, WindowOptions::default())
+            .unwrap_or_else(|e| {
+                panic!("{}", e);
+            });
+        window.set_target_fps(60);
+
+        let mut job = job::start(&scene);
+        let mut last_frame: Option<Instant> = None;
+        let mut finished_at: Option<Instant> = None;
+        let mut snapshots = 0;
+        let mut title = String::new();
+
+        loop {
+            if !window.is_open() || window.is_key_down(Key::Q) {
+                return Exit::Quit;
+            }
+            if window.is_key_down(Key::Escape) {
+                return Exit::Closed;
+            }
+
+            let progress = job.progress();
+            if window.is_key_pressed(Key::Space, KeyRepeat::No) {
+                progress.set_paused(!progress.is_paused());
+            }
+            if window.is_key_pressed(Key::S, KeyRepeat::No) {
+                snapshots += 1;
+                let path = format!("snapshot-{}-{}.png", scene.seed.unwrap_or(0), snapshots);
+                match job.screen().lock().unwrap().save_png(&path) {
+                    Ok(()) => println!("saved {}", path),
+                    Err(e) => eprintln!("{}: {}", path, e),
+                }
+            }
+            if window.is_key_pressed(Key::R, KeyRepeat::No) {
+                scene.seed = Some(rand::random::<u64>());
+                println!("seed: {}", scene.seed.unwrap());
+                job = restart(job, &scene);
+                last_frame = None;
+                finished_at = None;
+                continue;
+            }
+
+            let finished = job.is_finished();
+            if finished && finished_at.is_none() {
+                finished_at = Some(Instant::now());
+                last_frame = None;
+            }
+            if let (Some(t), Some(at)) = (opts.timeout, finished_at) {
+                if at.elapsed() >= t && !progress.is_paused() {
+                    return Exit::Timeout;
+                }
+            }
+
+            let state = if finished {
+                "done".to_string()
+            } else if progress.is_paused() {
+                format!("{:.0}% paused", progress.fraction() * 100.0)
+            } else {
+                format!("{:.0}%", progress.fraction() * 100.0)
+            };
+            let t = format!("seed {} - {} - {}", scene.seed.unwrap_or(0), state, KEYS);
+            if t != title {
+                window.set_title(&t);
+                title = t;
+            }
+
+            // repaint while drawing and once when done, otherwise only poll the keyboard
+            if last_frame.is_none_or(|at| !finished && at.elapsed() >= FRAME) {
+                let screen = job.screen();
+                let mut s = screen.lock().unwrap();
+                s.resolve();
+                window.update_with_buffer(s.buffer(), s.width(), s.height()).unwrap();
+                last_frame = Some(Instant::now());
+            } else {
+                window.update();
+            }
+        }
+    }
+
+    fn restart(job: RenderJob, scene: &Scene) -> RenderJob {
+        job.cancel();
+        drop(job);
+        job::start(scene)
+    }
+}